@@ -5,9 +5,10 @@ use bottomless_pit::render::RenderInformation;
 use bottomless_pit::texture::Texture;
 use bottomless_pit::vectors::Vec2;
 
-pub struct Anmiation {
-    sprite_sheet: Material,
-    sprite_size: Vec2<f32>,
+/// Which frame an animation is on without the sprite sheet, so things that share a sheet
+/// can each keep their own place in it and work without an engine
+#[derive(Clone, Copy, Debug)]
+pub struct AnimationClock {
     frames: usize,
     current_frame: usize,
     frame_time_counter: f32,
@@ -15,6 +16,55 @@ pub struct Anmiation {
     looping: bool,
 }
 
+impl AnimationClock {
+    pub fn new(frames: usize, frame_time: f32, looping: bool) -> Self {
+        Self {
+            frames,
+            current_frame: 0,
+            frame_time_counter: 0.0,
+            frame_time,
+            looping,
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.frame_time_counter += dt;
+
+        if self.frame_time_counter > self.frame_time {
+            if self.looping {
+                self.current_frame = (self.current_frame + 1) % self.frames;
+            } else if self.current_frame < self.frames - 1 {
+                self.current_frame += 1;
+            }
+        }
+
+        self.frame_time_counter = self.frame_time_counter % self.frame_time;
+    }
+
+    pub fn reset(&mut self) {
+        self.current_frame = 0;
+        self.frame_time_counter = 0.0;
+    }
+
+    pub fn get_frame(&self) -> usize {
+        self.current_frame
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames
+    }
+
+    pub fn is_done(&self) -> bool {
+        !self.looping && self.current_frame == self.frames - 1
+    }
+}
+
+pub struct Anmiation {
+    sprite_sheet: Material,
+    sprite_size: Vec2<f32>,
+    clock: AnimationClock,
+}
+
 impl Anmiation {
     pub fn new(texture_path: &str, sprite_size: Vec2<f32>, frames: usize, frame_time: f32, looping: bool, engine: &mut Engine) -> Self {
         // let texture = engine_handle.create_texture(texture_path).unwrap();
@@ -26,45 +76,53 @@ impl Anmiation {
         Self {
             sprite_sheet,
             sprite_size,
-            frames,
-            current_frame: 0,
-            frame_time_counter: 0.0,
-            frame_time,
-            looping,
+            clock: AnimationClock::new(frames, frame_time, looping),
         }
     }
 
     pub fn add_instance(&mut self, render_handle: &RenderInformation, draw_pos: Vec2<f32>, draw_size: Vec2<f32>, flipped: bool) {
-        let dir = if flipped {
-            -1.0
-        } else {
-            1.0
-        };
+        self.add_tinted_instance(render_handle, draw_pos, draw_size, flipped, Colour::WHITE);
+    }
 
-        self.sprite_sheet.add_rectangle_with_uv(
+    pub fn add_tinted_instance(&mut self, render_handle: &RenderInformation, draw_pos: Vec2<f32>, draw_size: Vec2<f32>, flipped: bool, tint: Colour) {
+        let frame = self.clock.get_frame();
+        self.add_tinted_instance_at_frame(render_handle, draw_pos, draw_size, flipped, frame, tint);
+    }
+
+    /// a still of `frame` turned by `deg`, for things that have stopped animating
+    pub fn add_still(&mut self, render_handle: &RenderInformation, draw_pos: Vec2<f32>, draw_size: Vec2<f32>, frame: usize, deg: f32, tint: Colour) {
+        let frame = frame.min(self.clock.frame_count() - 1);
+
+        self.sprite_sheet.add_rectangle_ex(
             draw_pos,
             draw_size,
-            Vec2{x: self.current_frame as f32 * self.sprite_size.x, y: 0.0},
-            Vec2{x: self.sprite_size.x * dir, y: self.sprite_size.y},
-            Colour::WHITE,
+            tint,
+            deg,
+            Vec2{x: frame as f32 * self.sprite_size.x, y: 0.0},
+            self.sprite_size,
             render_handle
         );
     }
 
-    pub fn add_with_rotation(&mut self, render_handle: &mut RenderInformation, draw_pos: Vec2<f32>, draw_size: Vec2<f32>, flipped: bool, deg: f32) {
+    pub fn add_instance_at_frame(&mut self, render_handle: &RenderInformation, draw_pos: Vec2<f32>, draw_size: Vec2<f32>, flipped: bool, frame: usize) {
+        self.add_tinted_instance_at_frame(render_handle, draw_pos, draw_size, flipped, frame, Colour::WHITE);
+    }
+
+    /// for when the frame comes from somewhere else, like an `AnimationClock`
+    pub fn add_tinted_instance_at_frame(&mut self, render_handle: &RenderInformation, draw_pos: Vec2<f32>, draw_size: Vec2<f32>, flipped: bool, frame: usize, tint: Colour) {
         let dir = if flipped {
             -1.0
         } else {
             1.0
         };
+        let frame = frame.min(self.clock.frame_count() - 1);
 
-        self.sprite_sheet.add_rectangle_ex(
+        self.sprite_sheet.add_rectangle_with_uv(
             draw_pos,
             draw_size,
-            Colour::WHITE,
-            deg,
-            Vec2{x: self.current_frame as f32 * self.sprite_size.x, y: 0.0},
+            Vec2{x: frame as f32 * self.sprite_size.x, y: 0.0},
             Vec2{x: self.sprite_size.x * dir, y: self.sprite_size.y},
+            tint,
             render_handle
         );
     }
@@ -74,25 +132,10 @@ impl Anmiation {
     }
 
     pub fn update(&mut self, dt: f32) {
-        self.frame_time_counter += dt;
-
-        if self.frame_time_counter > self.frame_time {
-            if self.looping {
-                self.current_frame = (self.current_frame + 1) % self.frames;
-            } else if self.current_frame < self.frames - 1 {
-                self.current_frame += 1;
-            }
-        }
-
-        self.frame_time_counter = self.frame_time_counter % self.frame_time;
-    }
-
-    pub fn reset(&mut self) {
-        self.current_frame = 0;
-        self.frame_time_counter = 0.0;
+        self.clock.update(dt);
     }
 
-    pub fn is_done(&self) -> bool {
-        !self.looping && self.current_frame == self.frames - 1
+    pub fn frame_count(&self) -> usize {
+        self.clock.frame_count()
     }
-}
\ No newline at end of file
+}
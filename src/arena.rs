@@ -0,0 +1,323 @@
+use bottomless_pit::colour::Colour;
+use bottomless_pit::material::Material;
+use bottomless_pit::render::RenderInformation;
+use bottomless_pit::vectors::Vec2;
+
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::camera::Camera;
+use crate::collision;
+use crate::hazard::{Hazards, Stovetop};
+use crate::viewport::LOGICAL_SIZE;
+
+/// how far past the edge chefs walk in from and back out to
+pub const OFF_ARENA: f32 = 50.0;
+
+// the built in arenas, baked into the binary so they also work on the web
+const ARENA_FILES: [(&str, &str); 4] = [
+    ("kitchen.arena", include_str!("../assets/arenas/kitchen.arena")),
+    ("diner.arena", include_str!("../assets/arenas/diner.arena")),
+    ("bakery.arena", include_str!("../assets/arenas/bakery.arena")),
+    ("banquet.arena", include_str!("../assets/arenas/banquet.arena")),
+];
+
+#[derive(Clone, Debug)]
+pub struct Arena {
+    pub name: String,
+    pub background: String,
+    pub tint: Colour,
+    pub size: Vec2<f32>,
+    pub spawn_edges: Vec<SpawnEdge>,
+    pub spawn_points: Vec<Vec2<f32>>,
+    pub obstacles: Vec<Obstacle>,
+    pub hazards: Hazards,
+}
+
+impl Arena {
+    pub fn load_all() -> Vec<Self> {
+        ARENA_FILES
+            .iter()
+            .map(|(file, source)| Self::parse(source).unwrap_or_else(|e| panic!("{}: {}", file, e)))
+            .collect()
+    }
+
+    /// Parses an arena definition. Every line is a keyword followed by its values,
+    /// blank lines and lines starting with `#` are skipped.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut name = None;
+        let mut background = None;
+        let mut tint = Colour::WHITE;
+        let mut size = None;
+        let mut spawn_edges = Vec::new();
+        let mut spawn_points = Vec::new();
+        let mut obstacles = Vec::new();
+        let mut stoves = Vec::new();
+
+        for (line_number, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+            let rest = rest.trim();
+            let error = |msg: &str| format!("line {}: {}", line_number + 1, msg);
+
+            match keyword {
+                "name" => name = Some(rest.to_string()),
+                "background" => background = Some(rest.to_string()),
+                "tint" => {
+                    let [r, g, b] = parse_numbers(rest).map_err(|e| error(&e))?;
+                    tint = Colour::from_rgba(r, g, b, 255.0);
+                },
+                "size" => {
+                    let [x, y] = parse_numbers(rest).map_err(|e| error(&e))?;
+                    size = Some(Vec2{x, y});
+                },
+                "spawn_edge" => spawn_edges.push(SpawnEdge::from_str(rest).ok_or_else(|| error("unknown spawn edge"))?),
+                "stove" => {
+                    let [x, y, w, h] = parse_numbers(rest).map_err(|e| error(&e))?;
+                    stoves.push(Stovetop::new(Vec2{x, y}, Vec2{x: w, y: h}, stoves.len()));
+                },
+                "spawn_point" => {
+                    let [x, y] = parse_numbers(rest).map_err(|e| error(&e))?;
+                    spawn_points.push(Vec2{x, y});
+                },
+                _ => {
+                    let kind = ObstacleKind::from_str(keyword).ok_or_else(|| error(&format!("unknown keyword `{}`", keyword)))?;
+                    let [x, y, w, h] = parse_numbers(rest).map_err(|e| error(&e))?;
+                    obstacles.push(Obstacle::new(kind, Vec2{x, y}, Vec2{x: w, y: h}));
+                },
+            }
+        }
+
+        if spawn_edges.is_empty() && spawn_points.is_empty() {
+            return Err("arena needs at least one spawn_edge or spawn_point".to_string());
+        }
+
+        Ok(Self {
+            name: name.ok_or("arena is missing a name")?,
+            background: background.ok_or("arena is missing a background")?,
+            tint,
+            size: size.ok_or("arena is missing a size")?,
+            spawn_edges,
+            spawn_points,
+            obstacles,
+            hazards: Hazards::new(stoves),
+        })
+    }
+
+    /// picks somewhere just outside the arena (or one of the fixed doors) for a chef to walk in from
+    pub fn spawn_position(&self, rand: &mut ChaCha8Rng) -> Vec2<f32> {
+        let choice = rand.gen_range(0..self.spawn_edges.len() + self.spawn_points.len());
+        if choice >= self.spawn_edges.len() {
+            return self.spawn_points[choice - self.spawn_edges.len()];
+        }
+
+        match self.spawn_edges[choice] {
+            SpawnEdge::Left => Vec2{x: -OFF_ARENA, y: rand.gen_range(0.0..self.size.y)},
+            SpawnEdge::Right => Vec2{x: self.size.x + OFF_ARENA, y: rand.gen_range(0.0..self.size.y)},
+            SpawnEdge::Top => Vec2{x: rand.gen_range(0.0..self.size.x), y: -OFF_ARENA},
+            SpawnEdge::Bottom => Vec2{x: rand.gen_range(0.0..self.size.x), y: self.size.y + OFF_ARENA},
+        }
+    }
+
+    pub fn draw(&self, render_handle: &mut RenderInformation, material: &mut Material, camera: &Camera) {
+        self.hazards.draw(render_handle, material, camera);
+        self.obstacles.iter().for_each(|o| o.draw(render_handle, material, camera));
+    }
+
+    /// whether the whole thing can be seen without the camera moving
+    pub fn fits_on_screen(&self) -> bool {
+        self.size.x <= LOGICAL_SIZE.x && self.size.y <= LOGICAL_SIZE.y
+    }
+
+    pub fn contains(&self, pos: Vec2<f32>) -> bool {
+        pos.x > 0.0 && pos.x < self.size.x && pos.y > 0.0 && pos.y < self.size.y
+    }
+
+    pub fn obstacle_rects(&self) -> Vec<(Vec2<f32>, Vec2<f32>)> {
+        self.obstacles.iter().map(|o| (o.pos, o.size)).collect()
+    }
+
+    /// pushes the rect out of every obstacle it overlaps and returns the new position
+    pub fn push_out(&self, pos: Vec2<f32>, size: Vec2<f32>) -> Vec2<f32> {
+        let mut pos = pos;
+        for obstacle in self.obstacles.iter() {
+            if let Some(push) = collision::resolve_rect(size, pos, obstacle.size, obstacle.pos) {
+                pos = pos + push;
+            }
+        }
+        pos
+    }
+
+    pub fn line_of_sight(&self, from: Vec2<f32>, to: Vec2<f32>) -> bool {
+        !self.obstacles.iter().any(|o| collision::segment_rect(from, to, o.size, o.pos))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpawnEdge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl SpawnEdge {
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "left" => Some(Self::Left),
+            "right" => Some(Self::Right),
+            "top" => Some(Self::Top),
+            "bottom" => Some(Self::Bottom),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Obstacle {
+    pub kind: ObstacleKind,
+    pub pos: Vec2<f32>,
+    pub size: Vec2<f32>,
+}
+
+impl Obstacle {
+    pub fn new(kind: ObstacleKind, pos: Vec2<f32>, size: Vec2<f32>) -> Self {
+        Self {
+            kind,
+            pos,
+            size,
+        }
+    }
+
+    pub fn draw(&self, render_handle: &mut RenderInformation, material: &mut Material, camera: &Camera) {
+        let (pos, size) = camera.rect(self.pos, self.size);
+        material.add_rectangle(pos, size, self.kind.colour(), render_handle);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ObstacleKind {
+    Counter,
+    Oven,
+    Table,
+}
+
+impl ObstacleKind {
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "counter" => Some(Self::Counter),
+            "oven" => Some(Self::Oven),
+            "table" => Some(Self::Table),
+            _ => None,
+        }
+    }
+
+    /// ovens are metal so butter bounces off them, everything else soaks it up
+    pub fn deflects(&self) -> bool {
+        matches!(self, Self::Oven)
+    }
+
+    fn colour(&self) -> Colour {
+        match self {
+            Self::Counter => Colour::from_rgba(150.0, 110.0, 70.0, 255.0),
+            Self::Oven => Colour::from_rgba(70.0, 70.0, 80.0, 255.0),
+            Self::Table => Colour::from_rgba(200.0, 160.0, 110.0, 255.0),
+        }
+    }
+}
+
+/// `N` whitespace separated numbers, no more and no less
+pub fn parse_numbers<const N: usize>(s: &str) -> Result<[f32; N], String> {
+    let mut numbers = [0.0; N];
+    let mut parts = s.split_whitespace();
+    for number in numbers.iter_mut() {
+        let part = parts.next().ok_or(format!("expected {} numbers", N))?;
+        *number = part.parse().map_err(|_| format!("`{}` is not a number", part))?;
+    }
+
+    if parts.next().is_some() {
+        return Err(format!("expected {} numbers", N));
+    }
+
+    Ok(numbers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL: &str = "
+# a tiny test kitchen
+name Test
+background assets/bg.png
+tint 200 180 160
+size 400 300
+
+spawn_edge left
+spawn_point 200 -50
+counter 10 20 30 40
+stove 100 100 50 50
+";
+
+    #[test]
+    fn parses_a_whole_file() {
+        let arena = Arena::parse(SMALL).unwrap();
+
+        assert_eq!(arena.name, "Test");
+        assert_eq!(arena.background, "assets/bg.png");
+        assert_eq!((arena.size.x, arena.size.y), (400.0, 300.0));
+        assert_eq!(arena.spawn_edges, vec![SpawnEdge::Left]);
+        assert_eq!(arena.spawn_points.len(), 1);
+        assert_eq!(arena.obstacles.len(), 1);
+        assert_eq!(arena.obstacles[0].kind, ObstacleKind::Counter);
+        assert_eq!((arena.obstacles[0].size.x, arena.obstacles[0].size.y), (30.0, 40.0));
+        assert!(arena.fits_on_screen());
+    }
+
+    #[test]
+    fn built_in_arenas_parse() {
+        assert_eq!(Arena::load_all().len(), ARENA_FILES.len());
+    }
+
+    #[test]
+    fn unknown_keyword_is_an_error() {
+        let source = format!("{}\nfridge 1 2 3 4", SMALL);
+        let error = Arena::parse(&source).err().unwrap();
+        assert!(error.contains("unknown keyword `fridge`"), "{}", error);
+    }
+
+    #[test]
+    fn unknown_spawn_edge_is_an_error() {
+        let source = format!("{}\nspawn_edge middle", SMALL);
+        assert!(Arena::parse(&source).err().unwrap().contains("unknown spawn edge"));
+    }
+
+    #[test]
+    fn malformed_numbers_are_errors() {
+        let bad_number = SMALL.replace("size 400 300", "size 400 wide");
+        let error = Arena::parse(&bad_number).err().unwrap();
+        assert!(error.starts_with("line 6:") && error.contains("`wide` is not a number"), "{}", error);
+
+        let too_few = SMALL.replace("counter 10 20 30 40", "counter 10 20 30");
+        assert!(Arena::parse(&too_few).err().unwrap().contains("expected 4 numbers"));
+
+        let too_many = SMALL.replace("size 400 300", "size 400 300 200");
+        assert!(Arena::parse(&too_many).err().unwrap().contains("expected 2 numbers"));
+    }
+
+    #[test]
+    fn missing_fields_are_errors() {
+        let no_size = SMALL.replace("size 400 300", "");
+        assert_eq!(Arena::parse(&no_size).err().unwrap(), "arena is missing a size");
+
+        let no_name = SMALL.replace("name Test", "");
+        assert_eq!(Arena::parse(&no_name).err().unwrap(), "arena is missing a name");
+
+        let no_spawns = SMALL.replace("spawn_edge left", "").replace("spawn_point 200 -50", "");
+        assert!(Arena::parse(&no_spawns).err().unwrap().contains("spawn_edge or spawn_point"));
+    }
+}
@@ -0,0 +1,311 @@
+use crate::event::{GameEvent, HitTarget};
+
+/// Everything the game makes a noise for
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sound {
+    ButterThrown,
+    ParryCharged,
+    ParryReleased,
+    ReflectHit,
+    ChefDefeated,
+    PlayerHit,
+    WaveStart,
+    WaveEnd,
+    Death,
+}
+
+impl Sound {
+    /// only the rodio backend needs to go through every sound
+    #[cfg(feature = "sound")]
+    pub const ALL: [Self; 9] = [
+        Self::ButterThrown,
+        Self::ParryCharged,
+        Self::ParryReleased,
+        Self::ReflectHit,
+        Self::ChefDefeated,
+        Self::PlayerHit,
+        Self::WaveStart,
+        Self::WaveEnd,
+        Self::Death,
+    ];
+
+    /// what to play when `event` happens, if anything
+    pub fn for_event(event: &GameEvent) -> Option<Self> {
+        match event {
+            GameEvent::ButterThrown => Some(Self::ButterThrown),
+            GameEvent::ParryCharged => Some(Self::ParryCharged),
+            GameEvent::ParrySwung => Some(Self::ParryReleased),
+            GameEvent::Hit{target: HitTarget::Enemy(_), ..} => Some(Self::ReflectHit),
+            GameEvent::Hit{target: HitTarget::Player(_), ..} => Some(Self::PlayerHit),
+            GameEvent::Kill{..} => Some(Self::ChefDefeated),
+            GameEvent::WaveStarted => Some(Self::WaveStart),
+            GameEvent::WaveCleared{..} => Some(Self::WaveEnd),
+            GameEvent::PlayerDied{..} => Some(Self::Death),
+            GameEvent::Reflect{..} | GameEvent::PickupCollected{..} => None,
+        }
+    }
+
+    #[cfg(feature = "sound")]
+    pub fn file(&self) -> &'static str {
+        match self {
+            Self::ButterThrown => "assets/sounds/throw.wav",
+            Self::ParryCharged => "assets/sounds/charge.wav",
+            Self::ParryReleased => "assets/sounds/release.wav",
+            Self::ReflectHit => "assets/sounds/reflect_hit.wav",
+            Self::ChefDefeated => "assets/sounds/chef_down.wav",
+            Self::PlayerHit => "assets/sounds/player_hit.wav",
+            Self::WaveStart => "assets/sounds/wave_start.wav",
+            Self::WaveEnd => "assets/sounds/wave_end.wav",
+            Self::Death => "assets/sounds/death.wav",
+        }
+    }
+}
+
+/// The music is one loop split into layers that all play together, later waves turn more of them up
+#[cfg(feature = "sound")]
+pub const MUSIC_LAYERS: [&str; 3] = [
+    "assets/sounds/music_base.wav",
+    "assets/sounds/music_drums.wav",
+    "assets/sounds/music_lead.wav",
+];
+
+/// how loud each music layer is on `wave`, `None` is for the menus
+pub fn music_volumes(wave: Option<u32>) -> [f32; 3] {
+    let Some(wave) = wave else {
+        return [0.3, 0.0, 0.0];
+    };
+
+    let wave = wave as f32;
+    [
+        0.5,
+        ((wave - 2.0) / 3.0).clamp(0.0, 0.5),
+        ((wave - 5.0) / 4.0).clamp(0.0, 0.4),
+    ]
+}
+
+/// Whatever actually makes the noise
+pub trait AudioBackend {
+    fn play(&mut self, sound: Sound);
+    fn set_music_volumes(&mut self, volumes: [f32; 3]);
+}
+
+/// Plays nothing and just remembers what it was asked to, for running without a sound
+/// device or without the `sound` feature
+#[derive(Debug, Default)]
+pub struct NullBackend {
+    pub played: Vec<Sound>,
+    pub music_volumes: [f32; 3],
+}
+
+impl AudioBackend for NullBackend {
+    fn play(&mut self, sound: Sound) {
+        self.played.push(sound);
+    }
+
+    fn set_music_volumes(&mut self, volumes: [f32; 3]) {
+        self.music_volumes = volumes;
+    }
+}
+
+pub struct Audio {
+    backend: Box<dyn AudioBackend>,
+    music_wave: Option<Option<u32>>,
+}
+
+impl Audio {
+    /// real sound when there's a device to play it on, silence otherwise
+    pub fn new() -> Self {
+        #[cfg(feature = "sound")]
+        if let Some(backend) = rodio_backend::RodioBackend::new() {
+            return Self::with_backend(Box::new(backend));
+        }
+
+        Self::with_backend(Box::new(NullBackend::default()))
+    }
+
+    pub fn with_backend(backend: Box<dyn AudioBackend>) -> Self {
+        Self {
+            backend,
+            music_wave: None,
+        }
+    }
+
+    /// plays a sound for everything that happened this frame, the same sound twice only plays once
+    pub fn play_events(&mut self, events: &[GameEvent]) {
+        let mut played = Vec::new();
+        for sound in events.iter().filter_map(Sound::for_event) {
+            if !played.contains(&sound) {
+                self.backend.play(sound);
+                played.push(sound);
+            }
+        }
+    }
+
+    /// `None` for the menus
+    pub fn set_wave(&mut self, wave: Option<u32>) {
+        if self.music_wave == Some(wave) {
+            return;
+        }
+
+        self.music_wave = Some(wave);
+        self.backend.set_music_volumes(music_volumes(wave));
+    }
+}
+
+#[cfg(feature = "sound")]
+mod rodio_backend {
+    use std::fs;
+    use std::io::Cursor;
+
+    use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+
+    use super::{AudioBackend, Sound, MUSIC_LAYERS};
+
+    pub struct RodioBackend {
+        // dropping the stream stops all sound so it has to live as long as we do
+        _stream: OutputStream,
+        handle: OutputStreamHandle,
+        /// the raw wav for each sound in `Sound::ALL` order, a missing file is just silent
+        effects: Vec<Option<Vec<u8>>>,
+        music: Vec<Sink>,
+    }
+
+    impl RodioBackend {
+        pub fn new() -> Option<Self> {
+            let (stream, handle) = OutputStream::try_default().ok()?;
+            let effects = Sound::ALL.iter().map(|s| fs::read(s.file()).ok()).collect();
+
+            // every layer starts at once and they're the same length so they stay in time
+            let music = MUSIC_LAYERS
+                .iter()
+                .filter_map(|path| {
+                    let bytes = fs::read(path).ok()?;
+                    let source = Decoder::new_looped(Cursor::new(bytes)).ok()?;
+                    let sink = Sink::try_new(&handle).ok()?;
+                    sink.set_volume(0.0);
+                    sink.append(source);
+                    Some(sink)
+                })
+                .collect();
+
+            Some(Self {
+                _stream: stream,
+                handle,
+                effects,
+                music,
+            })
+        }
+    }
+
+    impl AudioBackend for RodioBackend {
+        fn play(&mut self, sound: Sound) {
+            let Some(index) = Sound::ALL.iter().position(|s| *s == sound) else {
+                return;
+            };
+            let Some(bytes) = self.effects[index].clone() else {
+                return;
+            };
+
+            if let Ok(source) = Decoder::new(Cursor::new(bytes)) {
+                let _ = self.handle.play_raw(source.convert_samples());
+            }
+        }
+
+        fn set_music_volumes(&mut self, volumes: [f32; 3]) {
+            self.music.iter().zip(volumes).for_each(|(sink, volume)| sink.set_volume(volume));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use bottomless_pit::vectors::Vec2;
+
+    use super::*;
+    use crate::event::KillCause;
+    use crate::pickup::PickupKind;
+
+    /// lets the test keep looking at the backend after `Audio` takes it
+    impl AudioBackend for Rc<RefCell<NullBackend>> {
+        fn play(&mut self, sound: Sound) {
+            self.borrow_mut().play(sound);
+        }
+
+        fn set_music_volumes(&mut self, volumes: [f32; 3]) {
+            self.borrow_mut().set_music_volumes(volumes);
+        }
+    }
+
+    fn audio() -> (Audio, Rc<RefCell<NullBackend>>) {
+        let backend = Rc::new(RefCell::new(NullBackend::default()));
+        (Audio::with_backend(Box::new(backend.clone())), backend)
+    }
+
+    const POS: Vec2<f32> = Vec2{x: 10.0, y: 20.0};
+
+    #[test]
+    fn events_map_to_sounds() {
+        let cases = [
+            (GameEvent::ButterThrown, Some(Sound::ButterThrown)),
+            (GameEvent::ParryCharged, Some(Sound::ParryCharged)),
+            (GameEvent::ParrySwung, Some(Sound::ParryReleased)),
+            (GameEvent::Hit{target: HitTarget::Enemy(3), pos: POS, damage: 30.0}, Some(Sound::ReflectHit)),
+            (GameEvent::Hit{target: HitTarget::Player(0), pos: POS, damage: 1.0}, Some(Sound::PlayerHit)),
+            (GameEvent::Kill{pos: POS, cause: KillCause::Stove}, Some(Sound::ChefDefeated)),
+            (GameEvent::WaveStarted, Some(Sound::WaveStart)),
+            (GameEvent::WaveCleared{wave: 2}, Some(Sound::WaveEnd)),
+            (GameEvent::PlayerDied{player: 0}, Some(Sound::Death)),
+            (GameEvent::Reflect{pos: POS, perfect: true}, None),
+            (GameEvent::PickupCollected{pos: POS, kind: PickupKind::Heart}, None),
+        ];
+
+        for (event, sound) in cases {
+            assert_eq!(Sound::for_event(&event), sound, "{:?}", event);
+        }
+    }
+
+    #[test]
+    fn a_sound_plays_once_per_frame() {
+        let (mut audio, backend) = audio();
+        let kill = GameEvent::Kill{pos: POS, cause: KillCause::Butter{nth: 0}};
+        let hit = GameEvent::Hit{target: HitTarget::Enemy(1), pos: POS, damage: 30.0};
+
+        audio.play_events(&[hit, kill, hit, GameEvent::Reflect{pos: POS, perfect: false}, kill]);
+        assert_eq!(backend.borrow().played, vec![Sound::ReflectHit, Sound::ChefDefeated]);
+
+        // the next frame can play them again
+        audio.play_events(&[kill]);
+        assert_eq!(backend.borrow().played, vec![Sound::ReflectHit, Sound::ChefDefeated, Sound::ChefDefeated]);
+    }
+
+    #[test]
+    fn music_only_changes_with_the_wave() {
+        let (mut audio, backend) = audio();
+        let changes = |audio: &mut Audio, wave| {
+            backend.borrow_mut().music_volumes = [-1.0; 3];
+            audio.set_wave(wave);
+            backend.borrow().music_volumes != [-1.0; 3]
+        };
+
+        assert!(changes(&mut audio, None));
+        assert_eq!(backend.borrow().music_volumes, music_volumes(None));
+        assert!(!changes(&mut audio, None));
+
+        assert!(changes(&mut audio, Some(1)));
+        assert!(!changes(&mut audio, Some(1)));
+        assert!(changes(&mut audio, Some(6)));
+        assert_eq!(backend.borrow().music_volumes, music_volumes(Some(6)));
+        assert!(changes(&mut audio, None));
+    }
+
+    #[test]
+    fn later_waves_are_louder() {
+        let quiet = music_volumes(Some(1));
+        let loud = music_volumes(Some(9));
+        assert!(quiet.iter().zip(loud).all(|(q, l)| *q <= l));
+        assert!(loud.iter().sum::<f32>() > quiet.iter().sum::<f32>());
+    }
+}
@@ -0,0 +1,196 @@
+use bottomless_pit::vectors::Vec2;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::event::{GameEvent, HitTarget, KillCause};
+use crate::viewport::{Viewport, LOGICAL_SIZE};
+
+/// furthest the screen gets thrown at full trauma
+const MAX_SHAKE: f32 = 14.0;
+/// trauma lost every second
+const TRAUMA_DECAY: f32 = 1.6;
+/// how quickly the camera catches up with whoever it's following, higher is snappier
+const FOLLOW_SPEED: f32 = 6.0;
+
+/// Turns world positions into window positions for everything that lives in the arena,
+/// the HUD only goes through the viewport.
+///
+/// Shake is driven by trauma, hits add some and it wears off over time. The offset grows
+/// with the square of it so small knocks barely move the screen and big ones really rattle it
+pub struct Camera {
+    /// the world position at the top left of the screen
+    pub offset: Vec2<f32>,
+    pub zoom: f32,
+    trauma: f32,
+    shake: Vec2<f32>,
+    shake_enabled: bool,
+    /// counts down while the game is frozen on a big hit
+    hit_stop: f32,
+    random: StdRng,
+    viewport: Viewport,
+}
+
+impl Camera {
+    pub fn new() -> Self {
+        Self {
+            offset: Vec2{x: 0.0, y: 0.0},
+            zoom: 1.0,
+            trauma: 0.0,
+            shake: Vec2{x: 0.0, y: 0.0},
+            shake_enabled: true,
+            hit_stop: 0.0,
+            random: StdRng::from_entropy(),
+            viewport: Viewport::new(),
+        }
+    }
+
+    /// runs on real time, hit-stop doesn't hold the camera still
+    pub fn update(&mut self, dt: f32) {
+        self.hit_stop = (self.hit_stop - dt).max(0.0);
+        self.trauma = (self.trauma - TRAUMA_DECAY * dt).max(0.0);
+
+        if !self.shake_enabled || self.trauma == 0.0 {
+            self.shake = Vec2{x: 0.0, y: 0.0};
+            return;
+        }
+
+        let amount = MAX_SHAKE * self.trauma.powi(2);
+        self.shake = Vec2{
+            x: self.random.gen_range(-1.0..=1.0) * amount,
+            y: self.random.gen_range(-1.0..=1.0) * amount,
+        };
+    }
+
+    /// shakes and freezes for whatever in `events` deserves it
+    pub fn handle_events(&mut self, events: &[GameEvent]) {
+        for event in events {
+            match event {
+                GameEvent::Hit{target: HitTarget::Player(_), ..} => {
+                    self.add_trauma(0.45);
+                    self.freeze(0.08);
+                },
+                GameEvent::Hit{target: HitTarget::Enemy(_), ..} => self.add_trauma(0.1),
+                // one butter taking out a second chef or more
+                GameEvent::Kill{cause: KillCause::Butter{nth}, ..} if *nth > 0 => {
+                    self.add_trauma(0.3);
+                    self.freeze(0.05);
+                },
+                GameEvent::PlayerDied{..} => {
+                    self.add_trauma(0.7);
+                    self.freeze(0.15);
+                },
+                _ => {},
+            }
+        }
+    }
+
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+
+    /// holds the game still for `seconds`, a longer freeze wins over a shorter one
+    pub fn freeze(&mut self, seconds: f32) {
+        self.hit_stop = self.hit_stop.max(seconds);
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.hit_stop > 0.0
+    }
+
+    pub fn set_shake_enabled(&mut self, enabled: bool) {
+        self.shake_enabled = enabled;
+        if !enabled {
+            self.shake = Vec2{x: 0.0, y: 0.0};
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.trauma = 0.0;
+        self.hit_stop = 0.0;
+        self.shake = Vec2{x: 0.0, y: 0.0};
+    }
+
+    /// eases towards having `target` in the middle of the screen without ever showing past the
+    /// edge of an arena `bounds` big. One that's smaller than the screen just sits in the middle
+    pub fn follow(&mut self, target: Vec2<f32>, bounds: Vec2<f32>, dt: f32) {
+        let goal = self.follow_goal(target, bounds);
+        let t = 1.0 - (-FOLLOW_SPEED * dt).exp();
+        self.offset = Vec2{
+            x: self.offset.x + (goal.x - self.offset.x) * t,
+            y: self.offset.y + (goal.y - self.offset.y) * t,
+        };
+    }
+
+    /// like `follow` but gets there straight away, for the start of a run
+    pub fn snap_to(&mut self, target: Vec2<f32>, bounds: Vec2<f32>) {
+        self.offset = self.follow_goal(target, bounds);
+    }
+
+    fn follow_goal(&self, target: Vec2<f32>, bounds: Vec2<f32>) -> Vec2<f32> {
+        let view = self.view_size();
+        let fit = |start: f32, room: f32| if room > 0.0 { start.clamp(0.0, room) } else { room / 2.0 };
+        Vec2{
+            x: fit(target.x - view.x / 2.0, bounds.x - view.x),
+            y: fit(target.y - view.y / 2.0, bounds.y - view.y),
+        }
+    }
+
+    /// how much of the world fits on the screen
+    pub fn view_size(&self) -> Vec2<f32> {
+        Vec2{x: LOGICAL_SIZE.x / self.zoom, y: LOGICAL_SIZE.y / self.zoom}
+    }
+
+    /// a world position on the logical screen, leaving out the shake
+    fn to_logical(&self, pos: Vec2<f32>) -> Vec2<f32> {
+        Vec2{x: (pos.x - self.offset.x) * self.zoom, y: (pos.y - self.offset.y) * self.zoom}
+    }
+
+    pub fn is_on_screen(&self, pos: Vec2<f32>) -> bool {
+        let logical = self.to_logical(pos);
+        logical.x >= 0.0 && logical.x <= LOGICAL_SIZE.x && logical.y >= 0.0 && logical.y <= LOGICAL_SIZE.y
+    }
+
+    /// For something off screen, the spot on the logical screen `margin` in from the edge that
+    /// lies in its direction. None when it's on screen already
+    pub fn edge_point(&self, pos: Vec2<f32>, margin: f32) -> Option<Vec2<f32>> {
+        if self.is_on_screen(pos) {
+            return None;
+        }
+
+        // walk out from the middle of the screen towards it until we hit the margin
+        let middle = Vec2{x: LOGICAL_SIZE.x / 2.0, y: LOGICAL_SIZE.y / 2.0};
+        let towards = self.to_logical(pos) - middle;
+        let reach = ((middle.x - margin) / towards.x.abs()).min((middle.y - margin) / towards.y.abs());
+        Some(Vec2{x: middle.x + towards.x * reach, y: middle.y + towards.y * reach})
+    }
+
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        self.viewport = viewport;
+    }
+
+    pub fn get_viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    pub fn to_screen(&self, pos: Vec2<f32>) -> Vec2<f32> {
+        self.viewport.to_screen(Vec2{
+            x: (pos.x - self.offset.x) * self.zoom + self.shake.x,
+            y: (pos.y - self.offset.y) * self.zoom + self.shake.y,
+        })
+    }
+
+    /// a window position, like the mouse, back into the world
+    pub fn to_world(&self, pos: Vec2<f32>) -> Vec2<f32> {
+        let logical = self.viewport.to_logical(pos);
+        Vec2{
+            x: (logical.x - self.shake.x) / self.zoom + self.offset.x,
+            y: (logical.y - self.shake.y) / self.zoom + self.offset.y,
+        }
+    }
+
+    /// a world rect as (pos, size) in the window
+    pub fn rect(&self, pos: Vec2<f32>, size: Vec2<f32>) -> (Vec2<f32>, Vec2<f32>) {
+        let scale = self.zoom * self.viewport.get_scale();
+        (self.to_screen(pos), Vec2{x: size.x * scale, y: size.y * scale})
+    }
+}
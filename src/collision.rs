@@ -25,4 +25,61 @@ pub fn rect_rect(r1_size: Vec2<f32>, r1_pos: Vec2<f32>, r2_size: Vec2<f32>, r2_p
     } else {
         false
     }
-}
\ No newline at end of file
+}
+
+/// returns how far r1 has to move to stop overlapping r2 along the shortest axis
+pub fn resolve_rect(r1_size: Vec2<f32>, r1_pos: Vec2<f32>, r2_size: Vec2<f32>, r2_pos: Vec2<f32>) -> Option<Vec2<f32>> {
+    let overlap_left = r1_pos.x + r1_size.x - r2_pos.x;
+    let overlap_right = r2_pos.x + r2_size.x - r1_pos.x;
+    let overlap_top = r1_pos.y + r1_size.y - r2_pos.y;
+    let overlap_bottom = r2_pos.y + r2_size.y - r1_pos.y;
+
+    if overlap_left <= 0.0 || overlap_right <= 0.0 || overlap_top <= 0.0 || overlap_bottom <= 0.0 {
+        return None;
+    }
+
+    let push_x = if overlap_left < overlap_right { -overlap_left } else { overlap_right };
+    let push_y = if overlap_top < overlap_bottom { -overlap_top } else { overlap_bottom };
+
+    if push_x.abs() < push_y.abs() {
+        Some(Vec2{x: push_x, y: 0.0})
+    } else {
+        Some(Vec2{x: 0.0, y: push_y})
+    }
+}
+
+pub fn segment_rect(start: Vec2<f32>, end: Vec2<f32>, rect_size: Vec2<f32>, rect_pos: Vec2<f32>) -> bool {
+    // liang-barsky clipping, if any part of the segment survives it went through the rect
+    let dx = end.x - start.x;
+    let dy = end.y - start.y;
+    let checks = [
+        (-dx, start.x - rect_pos.x),
+        (dx, rect_pos.x + rect_size.x - start.x),
+        (-dy, start.y - rect_pos.y),
+        (dy, rect_pos.y + rect_size.y - start.y),
+    ];
+
+    let mut t_min: f32 = 0.0;
+    let mut t_max: f32 = 1.0;
+    for (p, q) in checks {
+        if p == 0.0 {
+            if q < 0.0 {
+                return false;
+            }
+            continue;
+        }
+
+        let t = q / p;
+        if p < 0.0 {
+            t_min = t_min.max(t);
+        } else {
+            t_max = t_max.min(t);
+        }
+
+        if t_min > t_max {
+            return false;
+        }
+    }
+
+    true
+}
@@ -0,0 +1,160 @@
+use std::fmt;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use web_time::{SystemTime, UNIX_EPOCH};
+
+const MODIFIER_COUNT: usize = 2;
+
+/// Twists on a normal run, the daily challenge always has a couple of them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DailyModifier {
+    FastButter,
+    NoHearts,
+    OneHeart,
+    Swarm,
+    TankyChefs,
+}
+
+impl DailyModifier {
+    pub const ALL: [Self; 5] = [Self::FastButter, Self::NoHearts, Self::OneHeart, Self::Swarm, Self::TankyChefs];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::FastButter => "Hot Butter",
+            Self::NoHearts => "No Patching Up",
+            Self::OneHeart => "Glass Biscuit",
+            Self::Swarm => "Swarm",
+            Self::TankyChefs => "Tough Chefs",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::FastButter => "butter flies twice as fast",
+            Self::NoHearts => "lost hearts never come back",
+            Self::OneHeart => "start with a single heart",
+            Self::Swarm => "chefs show up twice as often",
+            Self::TankyChefs => "chefs take 50% more hits",
+        }
+    }
+}
+
+/// A day on the calendar (UTC so everyone's day flips over together)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    pub fn today() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Self::from_days((seconds / 86_400) as i64)
+    }
+
+    /// turns days since 1970-01-01 into a date, see http://howardhinnant.github.io/date_algorithms.html
+    fn from_days(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (year_of_era + era * 400 + if month <= 2 { 1 } else { 0 }) as i32;
+
+        Self {
+            year,
+            month,
+            day,
+        }
+    }
+
+    /// splitmix64 over the date so neighbouring days don't get similar seeds
+    fn seed(&self) -> u64 {
+        let mut z = (self.year as u64) * 10_000 + (self.month as u64) * 100 + self.day as u64;
+        z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Everything about a daily run comes from its date so every copy of the game
+/// plays the same one, even offline
+#[derive(Clone, Debug, PartialEq)]
+pub struct DailyChallenge {
+    pub date: Date,
+    pub seed: u64,
+    /// index into the arenas the game loaded
+    pub arena: usize,
+    pub modifiers: Vec<DailyModifier>,
+}
+
+impl DailyChallenge {
+    pub fn today(arena_count: usize) -> Self {
+        Self::for_date(Date::today(), arena_count)
+    }
+
+    pub fn for_date(date: Date, arena_count: usize) -> Self {
+        let seed = date.seed();
+        let mut random = ChaCha8Rng::seed_from_u64(seed);
+
+        let arena = random.gen_range(0..arena_count);
+        let mut pool = DailyModifier::ALL.to_vec();
+        let mut modifiers = Vec::new();
+        while modifiers.len() < MODIFIER_COUNT && !pool.is_empty() {
+            let i = random.gen_range(0..pool.len());
+            modifiers.push(pool.swap_remove(i));
+        }
+
+        Self {
+            date,
+            seed,
+            arena,
+            modifiers,
+        }
+    }
+
+    pub fn has(&self, modifier: DailyModifier) -> bool {
+        self.modifiers.contains(&modifier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_days_matches_the_calendar() {
+        assert_eq!(Date::from_days(0), Date{year: 1970, month: 1, day: 1});
+        assert_eq!(Date::from_days(19_782), Date{year: 2024, month: 2, day: 29});
+    }
+
+    #[test]
+    fn same_day_same_challenge() {
+        let date = Date{year: 2026, month: 10, day: 19};
+        assert_eq!(DailyChallenge::for_date(date, 4), DailyChallenge::for_date(date, 4));
+    }
+
+    #[test]
+    fn challenge_is_pinned_to_the_date() {
+        // ChaCha8 gives the same stream on every platform, so everyone playing on this
+        // day gets exactly this
+        let daily = DailyChallenge::for_date(Date{year: 2026, month: 10, day: 19}, 4);
+        assert_eq!(daily.arena, 2);
+        assert_eq!(daily.modifiers, vec![DailyModifier::FastButter, DailyModifier::OneHeart]);
+    }
+}
@@ -0,0 +1,142 @@
+use bottomless_pit::colour::Colour;
+use bottomless_pit::engine_handle::Engine;
+use bottomless_pit::render::RenderInformation;
+use bottomless_pit::vectors::Vec2;
+
+use crate::camera::Camera;
+use crate::event::{GameEvent, HitTarget};
+use crate::text::Text;
+use crate::viewport::Viewport;
+
+const LIFETIME: f32 = 0.8;
+/// units per second the numbers float up by
+const RISE_SPEED: f32 = 45.0;
+const FONT_SIZE: f32 = 28.0;
+/// past this the oldest number gets reused
+const MAX_NUMBERS: usize = 24;
+
+/// How hard a reflected butter hit, so players can see what holding the charge longer does.
+/// A bare 30 from the butter plus whatever the wind up added
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DamageTier {
+    Tap,
+    Solid,
+    Heavy,
+    Crushing,
+}
+
+impl DamageTier {
+    pub fn from_damage(damage: f32) -> Self {
+        if damage < 40.0 {
+            Self::Tap
+        } else if damage < 50.0 {
+            Self::Solid
+        } else if damage < 60.0 {
+            Self::Heavy
+        } else {
+            Self::Crushing
+        }
+    }
+
+    pub fn colour(&self) -> (f32, f32, f32) {
+        match self {
+            Self::Tap => (255.0, 255.0, 255.0),
+            Self::Solid => (255.0, 230.0, 80.0),
+            Self::Heavy => (255.0, 150.0, 40.0),
+            Self::Crushing => (240.0, 50.0, 50.0),
+        }
+    }
+}
+
+struct DamageNumber {
+    /// made in white so the tier colour can tint it
+    text: Text,
+    /// the middle of the text in the world
+    pos: Vec2<f32>,
+    colour: (f32, f32, f32),
+    age: f32,
+}
+
+impl DamageNumber {
+    fn is_alive(&self) -> bool {
+        self.age < LIFETIME
+    }
+}
+
+/// Numbers that pop out of chefs when butter hits them and float away
+pub struct DamageNumbers {
+    numbers: Vec<DamageNumber>,
+    viewport: Viewport,
+}
+
+impl DamageNumbers {
+    pub fn new() -> Self {
+        Self {
+            numbers: Vec::new(),
+            viewport: Viewport::new(),
+        }
+    }
+
+    pub fn spawn(&mut self, damage: f32, pos: Vec2<f32>, engine_handle: &mut Engine) {
+        let label = format!("{}", damage.round() as u32);
+        let colour = DamageTier::from_damage(damage).colour();
+
+        let slot = match self.numbers.iter().position(|n| !n.is_alive()) {
+            Some(slot) => slot,
+            None if self.numbers.len() < MAX_NUMBERS => {
+                let mut text = Text::new(&label, FONT_SIZE, pos, Colour::WHITE, engine_handle);
+                text.fit(&self.viewport, engine_handle);
+                self.numbers.push(DamageNumber{text, pos, colour, age: 0.0});
+                return;
+            },
+            None => {
+                let oldest = self.numbers.iter().enumerate().max_by(|(_, a), (_, b)| a.age.total_cmp(&b.age));
+                oldest.map(|(slot, _)| slot).unwrap_or(0)
+            },
+        };
+
+        let number = &mut self.numbers[slot];
+        number.text.change_text(&label, engine_handle);
+        number.pos = pos;
+        number.colour = colour;
+        number.age = 0.0;
+    }
+
+    /// a number for every chef that got hit in `events`
+    pub fn handle_events(&mut self, events: &[GameEvent], engine_handle: &mut Engine) {
+        for event in events {
+            if let GameEvent::Hit{target: HitTarget::Enemy(_), pos, damage} = event {
+                self.spawn(*damage, *pos, engine_handle);
+            }
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for number in self.numbers.iter_mut().filter(|n| n.is_alive()) {
+            number.pos.y -= RISE_SPEED * dt;
+            number.age += dt;
+        }
+    }
+
+    /// the text sticks around to be reused
+    pub fn clear(&mut self) {
+        self.numbers.iter_mut().for_each(|n| n.age = LIFETIME);
+    }
+
+    /// call whenever the window might have changed size
+    pub fn set_viewport(&mut self, viewport: Viewport, engine_handle: &mut Engine) {
+        self.viewport = viewport;
+        self.numbers.iter_mut().for_each(|n| n.text.fit(&viewport, engine_handle));
+    }
+
+    /// they fade out as they rise
+    pub fn draw<'p, 'o>(&'o mut self, render_handle: &mut RenderInformation<'p, 'o>, camera: &Camera) where 'o: 'p {
+        for number in self.numbers.iter_mut().filter(|n| n.is_alive()) {
+            let (r, g, b) = number.colour;
+            let alpha = 255.0 * (1.0 - number.age / LIFETIME);
+            let size = number.text.size;
+            let corner = Vec2{x: number.pos.x - size.x as f32 / 2.0, y: number.pos.y - size.y as f32 / 2.0};
+            number.text.draw_at(render_handle, camera.to_screen(corner), Colour::from_rgba(r, g, b, alpha));
+        }
+    }
+}
@@ -0,0 +1,98 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Nightmare,
+}
+
+impl Difficulty {
+    pub const ALL: [Self; 4] = [Self::Easy, Self::Normal, Self::Hard, Self::Nightmare];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Easy => "Easy",
+            Self::Normal => "Normal",
+            Self::Hard => "Hard",
+            Self::Nightmare => "Nightmare",
+        }
+    }
+
+    /// what gets written to the save file
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::Easy => "easy",
+            Self::Normal => "normal",
+            Self::Hard => "hard",
+            Self::Nightmare => "nightmare",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|d| d.key() == key)
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Easy => Self::Normal,
+            Self::Normal => Self::Hard,
+            Self::Hard => Self::Nightmare,
+            Self::Nightmare => Self::Easy,
+        }
+    }
+
+    pub fn enemy_hp(&self) -> f32 {
+        match self {
+            Self::Easy => 0.7,
+            Self::Normal => 1.0,
+            Self::Hard => 1.3,
+            Self::Nightmare => 1.7,
+        }
+    }
+
+    pub fn butter_speed(&self) -> f32 {
+        match self {
+            Self::Easy => 0.8,
+            Self::Normal => 1.0,
+            Self::Hard => 1.25,
+            Self::Nightmare => 1.5,
+        }
+    }
+
+    pub fn throw_cooldown(&self) -> f32 {
+        match self {
+            Self::Easy => 1.4,
+            Self::Normal => 1.0,
+            Self::Hard => 0.8,
+            Self::Nightmare => 0.6,
+        }
+    }
+
+    pub fn spawn_timer(&self) -> f32 {
+        match self {
+            Self::Easy => 1.3,
+            Self::Normal => 1.0,
+            Self::Hard => 0.8,
+            Self::Nightmare => 0.6,
+        }
+    }
+
+    pub fn player_hearts(&self) -> f32 {
+        match self {
+            Self::Easy => 5.0,
+            Self::Normal => 3.0,
+            Self::Hard => 3.0,
+            Self::Nightmare => 2.0,
+        }
+    }
+
+    /// how much bigger or smaller the area the pin catches butter in is
+    pub fn reflect_window(&self) -> f32 {
+        match self {
+            Self::Easy => 1.3,
+            Self::Normal => 1.0,
+            Self::Hard => 0.9,
+            Self::Nightmare => 0.8,
+        }
+    }
+}
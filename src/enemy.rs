@@ -1,45 +1,75 @@
-use web_time::Instant;
-
 use bottomless_pit::colour::Colour;
 use bottomless_pit::engine_handle::Engine;
 use bottomless_pit::material::Material;
 use bottomless_pit::render::RenderInformation;
 use bottomless_pit::vectors::Vec2;
-use rand::rngs::ThreadRng;
+use rand_chacha::ChaCha8Rng;
 use rand::Rng;
 
 use crate::animation::Anmiation;
+use crate::arena::{Arena, OFF_ARENA};
+use crate::camera::Camera;
+use crate::difficulty::Difficulty;
+use crate::event::{GameEvent, HitTarget, KillCause};
+use crate::perk::ButterEffects;
 use crate::{collision, move_towards};
 use crate::player::Player;
+use crate::steering;
 
+/// seconds a chef takes to fall over once they're taken out
+const KNOCKOUT_TIME: f32 = 0.35;
+/// seconds they lie there fading away afterwards
+const CORPSE_TIME: f32 = 0.9;
 
+#[derive(Clone, Debug)]
 pub struct Enemy {
     pub pos: Vec2<f32>,
     pub size: Vec2<f32>,
-    shot_cooldown: Instant,
+    id: u32,
+    /// false once the chef should be taken out of the level
     valid: bool,
-    speed: f32,
-    desision_timer: f32,
+    /// seconds since the chef was taken out, they stay around a little while as a corpse
+    knocked_out: Option<f32>,
+    behaviour: Behaviour,
+    state_timer: f32,
+    throw_cooldown: f32,
+    strafe_dir: f32,
     target_pos: Vec2<f32>,
+    /// which player we're after
+    target: usize,
+    velocity: Vec2<f32>,
+    tuning: EnemyTuning,
     hp: f32,
     current_animation: usize,
 }
 
 impl Enemy {
-    pub fn new(pos: Vec2<f32>) -> Self {
+    pub fn new(pos: Vec2<f32>, id: u32, tuning: EnemyTuning) -> Self {
         Self {
             pos,
             size: Vec2{x: 50.0, y: 50.0},
-            shot_cooldown: Instant::now(),
+            id,
             valid: true,
-            speed: 40.0,
-            desision_timer: 100.0,
+            knocked_out: None,
+            behaviour: Behaviour::Approach,
+            state_timer: 0.0,
+            throw_cooldown: tuning.throw_cooldown,
+            strafe_dir: 1.0,
             target_pos: Vec2{x: 0.0, y: 0.0},
-            hp: 65.0,
+            target: 0,
+            velocity: Vec2{x: 0.0, y: 0.0},
+            tuning,
+            hp: tuning.hp,
             current_animation: 0,
         }
     }
 
+    /// a chef that goes down after `hp` damage, for tests that line a few up
+    #[cfg(test)]
+    pub(crate) fn with_hp(pos: Vec2<f32>, id: u32, hp: f32) -> Self {
+        Self::new(pos, id, EnemyTuning{hp, ..EnemyTuning::DEFAULT})
+    }
+
     pub fn create_animations(engine_handle: &mut Engine) -> [Anmiation; 4] {
         [
             Anmiation::new("assets/chefWalk.png", Vec2{x: 170.0, y: 170.0}, 5, 1.0/6.0, true, engine_handle),
@@ -49,120 +79,447 @@ impl Enemy {
         ]
     }
 
-    pub fn draw(&self, render_handle: &mut RenderInformation, animations: &mut [Anmiation]) {
-        animations[self.current_animation].add_instance(render_handle, self.pos, self.size, false);
+    pub fn draw(&self, render_handle: &mut RenderInformation, animations: &mut [Anmiation], camera: &Camera) {
+        let (pos, size) = camera.rect(self.pos, self.size);
+        if let Some(time) = self.knocked_out {
+            // topple over then fade out where they fell
+            let fallen = (time / KNOCKOUT_TIME).min(1.0);
+            let deg = 90.0 * (1.0 - (1.0 - fallen).powi(2));
+            let alpha = 255.0 * (1.0 - ((time - KNOCKOUT_TIME) / CORPSE_TIME).clamp(0.0, 1.0));
+            animations[self.current_animation].add_still(render_handle, pos, size, 0, deg, Colour::from_rgba(255.0, 255.0, 255.0, alpha));
+        } else if self.behaviour == Behaviour::WindUp {
+            // play the throw from its first frame so every wind up reads the same
+            let progress = self.state_timer / self.tuning.wind_up_time;
+            let animation = &mut animations[self.current_animation];
+            let frame = (progress * animation.frame_count() as f32) as usize;
+            animation.add_instance_at_frame(render_handle, pos, size, false, frame);
+        } else {
+            animations[self.current_animation].add_instance(render_handle, pos, size, false);
+        }
     }
 
-    pub fn update(&mut self, dt: f32, player: &Player, butters: &mut Vec<Butter>, rand: &mut ThreadRng) {
-        if self.shot_cooldown.elapsed().as_secs_f32() > 2.0 {
-            butters.push(Butter::new(self.get_center(), player.get_center()));
-            self.shot_cooldown = Instant::now();
+    /// `neighbours` are the ids and centers of the other chefs
+    pub fn update(&mut self, dt: f32, players: &[Player], neighbours: &[(u32, Vec2<f32>)], arena: &Arena, butters: &mut Vec<Butter>, rand: &mut ChaCha8Rng) {
+        if self.update_knocked_out(dt) {
+            return;
         }
 
-        self.desision_timer += dt;
-        if self.desision_timer > 1.5 {
-            self.switch_target(player, rand);
-            self.desision_timer %= 1.5;
+        self.state_timer += dt;
+        self.throw_cooldown -= dt;
+
+        let Some(player) = self.pick_target(players, arena) else {
+            return;
+        };
+        let player_center = player.get_center();
+        let distance = distance(self.get_center(), player_center);
+        let can_see = arena.line_of_sight(self.get_center(), player_center);
+        let (min_range, max_range) = self.tuning.preferred_range;
+
+        match self.behaviour {
+            Behaviour::Approach => {
+                if distance < self.tuning.personal_space {
+                    self.change_behaviour(Behaviour::Retreat);
+                } else if distance <= max_range {
+                    self.start_strafe(rand);
+                } else {
+                    self.target_pos = player_center;
+                }
+            },
+            Behaviour::Strafe => {
+                if self.throw_cooldown <= 0.0 && can_see {
+                    self.change_behaviour(Behaviour::WindUp);
+                } else if self.throw_cooldown <= 0.0 {
+                    // something is in the way, go find a clear shot
+                    self.start_reposition(player_center, arena.size, rand);
+                } else if distance < min_range {
+                    self.change_behaviour(Behaviour::Retreat);
+                } else if distance > max_range + self.tuning.range_slack {
+                    self.change_behaviour(Behaviour::Approach);
+                } else if self.state_timer > self.tuning.strafe_time {
+                    if rand.gen::<f32>() < self.tuning.reposition_chance {
+                        self.start_reposition(player_center, arena.size, rand);
+                    } else {
+                        self.start_strafe(rand);
+                    }
+                } else {
+                    self.target_pos = self.strafe_point(player_center, distance);
+                }
+            },
+            Behaviour::Retreat => {
+                if distance >= min_range {
+                    self.start_strafe(rand);
+                } else {
+                    let center = self.get_center();
+                    self.target_pos = Vec2{
+                        x: center.x + (center.x - player_center.x),
+                        y: center.y + (center.y - player_center.y),
+                    };
+                }
+            },
+            Behaviour::Reposition => {
+                let arrived = distance_sq(self.get_center(), self.target_pos) < 100.0;
+                if distance < self.tuning.personal_space {
+                    self.change_behaviour(Behaviour::Retreat);
+                } else if arrived || self.state_timer > self.tuning.reposition_timeout {
+                    self.start_strafe(rand);
+                }
+            },
+            Behaviour::WindUp => {
+                if !can_see {
+                    self.start_reposition(player_center, arena.size, rand);
+                } else if self.state_timer >= self.tuning.wind_up_time {
+                    butters.push(Butter::new(self.get_center(), player_center, self.tuning.butter_speed));
+                    self.throw_cooldown = self.tuning.throw_cooldown;
+                    self.start_strafe(rand);
+                }
+            },
+            Behaviour::Flee => {},
         }
 
-        self.pos = move_towards(self.pos, self.target_pos, self.speed * dt);
-        
-        if self.pos == self.target_pos {
-            self.desision_timer = 0.0;
-            self.switch_target(player, rand);
+        if !self.behaviour.is_moving() {
+            // hold still for the throw but keep steering so we still get pushed out of a crowd
+            self.target_pos = self.get_center();
         }
-        
-        if self.shot_cooldown.elapsed().as_secs_f32() > 1.5 {
-            self.current_animation = 2;
-        } else {
-            self.current_animation = 0;
+        // walk around the players too so we never end up standing on them
+        let mut obstacles = arena.obstacle_rects();
+        obstacles.extend(players.iter().map(|p| p.get_body()));
+        let mut speed = self.tuning.speed * self.behaviour.speed_factor();
+        if arena.hazards.is_greasy(self.pos, self.size) {
+            speed *= GREASE_SLOWDOWN;
         }
+        let slippery = arena.hazards.is_slippery(self.pos, self.size);
+        self.steer(dt, speed, neighbours, &obstacles, slippery);
+        self.pos = arena.push_out(self.pos, self.size);
 
-        if player.get_center().y < self.pos.y {
+        self.current_animation = if self.behaviour == Behaviour::WindUp {
+            2
+        } else {
+            0
+        };
+
+        if player_center.y < self.pos.y {
             self.current_animation += 1;
         }
     }
 
-    pub fn dead_update(&mut self, dt: f32, player: &Player) {
-        self.pos = move_towards(self.pos, self.target_pos, self.speed * dt);
+    /// `bounds` is the arena size, we're gone once we've walked out of it
+    pub fn dead_update(&mut self, dt: f32, players: &[Player], bounds: Vec2<f32>) {
+        if self.update_knocked_out(dt) {
+            return;
+        }
+
+        self.steer(dt, self.tuning.speed * self.behaviour.speed_factor(), &[], &[], false);
 
         self.current_animation = 0;
-        if player.get_center().y > self.pos.y {
+        if players.iter().any(|p| p.get_center().y > self.pos.y) {
             self.current_animation += 1;
         }
 
-        self.valid = self.pos.x > -50.0 && self.pos.x < 850.0 && self.pos.y > -50.0 && self.pos.y < 850.0;
+        let center = self.get_center();
+        self.valid = center.x > -OFF_ARENA && center.x < bounds.x + OFF_ARENA && center.y > -OFF_ARENA && center.y < bounds.y + OFF_ARENA;
+    }
+
+    /// counts down the corpse, returns true while the chef is out
+    fn update_knocked_out(&mut self, dt: f32) -> bool {
+        let Some(time) = self.knocked_out.as_mut() else {
+            return false;
+        };
+
+        *time += dt;
+        if *time >= KNOCKOUT_TIME + CORPSE_TIME {
+            self.valid = false;
+        }
+        true
     }
 
     pub fn is_valid(&self) -> bool {
         self.valid
     }
 
-    pub fn on_hit(&mut self, damage: f32) {
+    /// still in the fight, chefs that have been taken out can't be hit and don't get in the way
+    pub fn is_alive(&self) -> bool {
+        self.valid && self.knocked_out.is_none()
+    }
+
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+
+    /// returns true if this was the hit that took the chef out
+    pub fn on_hit(&mut self, damage: f32) -> bool {
+        if !self.is_alive() {
+            return false;
+        }
+
         self.hp -= damage;
         if self.hp < 0.0 {
-            self.valid = false;
+            self.knocked_out = Some(0.0);
+            return true;
+        }
+
+        false
+    }
+
+    /// how much hp is left out of what it started with, None until it's been hurt
+    pub fn get_health(&self) -> Option<f32> {
+        if !self.is_alive() || self.hp >= self.tuning.hp {
+            return None;
         }
+        Some((self.hp / self.tuning.hp).max(0.0))
     }
 
     pub fn get_center(&self) -> Vec2<f32> {
         Vec2{x: self.pos.x + self.size.x/2.0, y: self.pos.y + self.size.y/2.0}
     }
 
-    pub fn walk_off(&mut self) {
-        let left_dist = self.pos.x + 50.0;
-        let right_dist = 850.0 - self.pos.x;
-        let top_dist = self.pos.y + 50.0;
-        let bottom_dist = 850.0 - self.pos.y;
+    /// heads for whichever edge of the arena is closest
+    pub fn flee(&mut self, bounds: Vec2<f32>) {
+        if self.behaviour == Behaviour::Flee {
+            return;
+        }
+        self.change_behaviour(Behaviour::Flee);
+
+        // aim a whole chef past the line dead_update checks, arriving slows them
+        // down so they'd never quite reach it otherwise
+        let center = self.get_center();
+        let past = OFF_ARENA + self.size.x;
+
+        let left_dist = center.x + OFF_ARENA;
+        let right_dist = bounds.x + OFF_ARENA - center.x;
+        let top_dist = center.y + OFF_ARENA;
+        let bottom_dist = bounds.y + OFF_ARENA - center.y;
     
         let clostest_x = left_dist.min(right_dist);
         let closest_y = top_dist.min(bottom_dist);
 
         if clostest_x < closest_y {
             if left_dist < right_dist {
-                self.target_pos = Vec2{x: -50.0, y: self.pos.y};
+                self.target_pos = Vec2{x: -past, y: center.y};
             } else {
-                self.target_pos = Vec2{x: 850.0, y: self.pos.y};
+                self.target_pos = Vec2{x: bounds.x + past, y: center.y};
             }
+        } else if top_dist < bottom_dist {
+            self.target_pos = Vec2{x: center.x, y: -past};
         } else {
-            if top_dist < bottom_dist {
-                self.target_pos = Vec2{x: self.pos.x, y: -50.0};
-            } else {
-                self.target_pos = Vec2{x: self.pos.x, y: 850.0};
+            self.target_pos = Vec2{x: center.x, y: bounds.y + past};
+        }
+    }
+
+    fn change_behaviour(&mut self, behaviour: Behaviour) {
+        self.behaviour = behaviour;
+        self.state_timer = 0.0;
+    }
+
+    fn start_strafe(&mut self, rand: &mut ChaCha8Rng) {
+        self.strafe_dir = if rand.gen::<bool>() { 1.0 } else { -1.0 };
+        self.change_behaviour(Behaviour::Strafe);
+    }
+
+    /// the closest player still standing, ones behind cover count as further away. We stick
+    /// with who we're chasing unless someone else is a good bit closer so we don't dither
+    fn pick_target<'a>(&mut self, players: &'a [Player], arena: &Arena) -> Option<&'a Player> {
+        let center = self.get_center();
+        let cost = |player: &Player| {
+            let mut cost = distance(center, player.get_center());
+            if !arena.line_of_sight(center, player.get_center()) {
+                cost += HIDDEN_TARGET_PENALTY;
             }
+            cost
+        };
+
+        let (closest, closest_player) = players
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| !p.is_dead())
+            .min_by(|(_, a), (_, b)| cost(a).total_cmp(&cost(b)))?;
+
+        let keep_current = players
+            .get(self.target)
+            .is_some_and(|p| !p.is_dead() && cost(p) < cost(closest_player) + TARGET_SWITCH_MARGIN);
+        if !keep_current {
+            self.target = closest;
+        }
+
+        players.get(self.target)
+    }
+
+    fn start_reposition(&mut self, player_center: Vec2<f32>, bounds: Vec2<f32>, rand: &mut ChaCha8Rng) {
+        // pick a new spot on the preferred ring around the player
+        let (min_range, max_range) = self.tuning.preferred_range;
+        let angle: f32 = rand.gen_range(0.0..std::f32::consts::TAU);
+        let range: f32 = rand.gen_range(min_range..max_range);
+        let x = (player_center.x + angle.cos() * range).clamp(0.0, bounds.x - self.size.x);
+        let y = (player_center.y + angle.sin() * range).clamp(0.0, bounds.y - self.size.y);
+        self.target_pos = Vec2{x, y};
+        self.change_behaviour(Behaviour::Reposition);
+    }
+
+    fn strafe_point(&self, player_center: Vec2<f32>, distance: f32) -> Vec2<f32> {
+        // sidestep around the player while pulling back towards the middle of our range
+        let center = self.get_center();
+        let (min_range, max_range) = self.tuning.preferred_range;
+        let to_player = Vec2{x: (player_center.x - center.x) / distance, y: (player_center.y - center.y) / distance};
+        let side = Vec2{x: -to_player.y * self.strafe_dir, y: to_player.x * self.strafe_dir};
+        let range_error = distance - (min_range + max_range) / 2.0;
+
+        Vec2{
+            x: center.x + side.x * 100.0 + to_player.x * range_error,
+            y: center.y + side.y * 100.0 + to_player.y * range_error,
         }
     }
 
-    fn switch_target(&mut self, player: &Player, rand: &mut ThreadRng) {
-        // very sophisticated AI
-        let rng = rand.gen::<f32>();
-        if rng <= 0.333 {
-            self.target_pos = player.get_center();
-        } else if rng <= 0.666 {
-            let player_pos = player.get_center();
-            let x: f32 = (rand.gen_range(0.0..300.0) * (-1.0 * u8::from(rand.gen_range::<u8, _>(1..3) == 1) as f32)) + player_pos.x;
-            let y = (300.0 - x * (-1.0 * u8::from(rand.gen_range::<u8, _>(1..3) == 1) as f32)) + player_pos.y;
-            self.target_pos = Vec2{x, y};
+    fn steer(&mut self, dt: f32, max_speed: f32, neighbours: &[(u32, Vec2<f32>)], obstacles: &[(Vec2<f32>, Vec2<f32>)], slippery: bool) {
+        let center = self.get_center();
+        let tuning = &self.tuning;
+
+        let arrive = steering::arrive(center, self.velocity, self.target_pos, max_speed, tuning.slowing_radius);
+        let separate = steering::separate(center, self.id, neighbours, tuning.separation_radius);
+        let avoid = steering::avoid_obstacles(center, self.velocity, obstacles, tuning.look_ahead, self.size.x / 2.0);
+
+        let force = arrive
+            + steering::scale(separate, tuning.separation_weight)
+            + steering::scale(avoid, tuning.avoidance_weight);
+        // on butter we can barely change direction and just slide along
+        let agility = if slippery {
+            tuning.agility * 0.15
         } else {
-            let x: f32 = rand.gen_range(0.0..800.0);
-            let y: f32 = rand.gen_range(0.0..800.0);
-            self.target_pos = Vec2{x, y};
+            tuning.agility
+        };
+        let accel = steering::truncate(steering::scale(force, agility), tuning.max_force);
+
+        self.velocity = steering::truncate(self.velocity + steering::scale(accel, dt), max_speed);
+        self.pos = self.pos + steering::scale(self.velocity, dt);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Behaviour {
+    Approach,
+    Strafe,
+    Retreat,
+    Reposition,
+    WindUp,
+    Flee,
+}
+
+impl Behaviour {
+    fn is_moving(&self) -> bool {
+        *self != Self::WindUp
+    }
+
+    fn speed_factor(&self) -> f32 {
+        match self {
+            Self::Approach => 1.0,
+            Self::Strafe => 0.75,
+            Self::Retreat => 1.1,
+            Self::Reposition => 1.0,
+            Self::WindUp => 0.0,
+            Self::Flee => 1.5,
+        }
+    }
+}
+
+/// Knobs for the chef behaviour state machine, distances are in pixels and times in seconds
+#[derive(Clone, Copy, Debug)]
+pub struct EnemyTuning {
+    pub hp: f32,
+    pub speed: f32,
+    pub butter_speed: f32,
+    /// the chef tries to keep the player between these two distances
+    pub preferred_range: (f32, f32),
+    /// closer than this and the chef backs off no matter what it was doing
+    pub personal_space: f32,
+    /// how far past the preferred range we strafe before walking back in
+    pub range_slack: f32,
+    pub throw_cooldown: f32,
+    /// how long the throw animation plays before the butter leaves the hand
+    pub wind_up_time: f32,
+    pub strafe_time: f32,
+    pub reposition_chance: f32,
+    pub reposition_timeout: f32,
+    /// how quickly the chef matches the velocity it wants, per second
+    pub agility: f32,
+    pub max_force: f32,
+    /// distance from the target where the chef starts slowing down
+    pub slowing_radius: f32,
+    pub separation_radius: f32,
+    pub separation_weight: f32,
+    pub look_ahead: f32,
+    pub avoidance_weight: f32,
+}
+
+impl EnemyTuning {
+    pub const DEFAULT: Self = Self {
+        hp: 65.0,
+        speed: 40.0,
+        butter_speed: 100.0,
+        preferred_range: (180.0, 320.0),
+        personal_space: 90.0,
+        range_slack: 80.0,
+        throw_cooldown: 1.2,
+        wind_up_time: 5.0/6.0,
+        strafe_time: 2.0,
+        reposition_chance: 0.4,
+        reposition_timeout: 4.0,
+        agility: 5.0,
+        max_force: 200.0,
+        slowing_radius: 40.0,
+        separation_radius: 70.0,
+        separation_weight: 1.5,
+        look_ahead: 60.0,
+        avoidance_weight: 2.0,
+    };
+
+    pub fn for_difficulty(difficulty: Difficulty) -> Self {
+        let default = Self::DEFAULT;
+        Self {
+            hp: default.hp * difficulty.enemy_hp(),
+            butter_speed: default.butter_speed * difficulty.butter_speed(),
+            throw_cooldown: default.throw_cooldown * difficulty.throw_cooldown(),
+            ..default
         }
     }
 }
 
+fn distance_sq(a: Vec2<f32>, b: Vec2<f32>) -> f32 {
+    (a.x - b.x).powi(2) + (a.y - b.y).powi(2)
+}
+
+fn distance(a: Vec2<f32>, b: Vec2<f32>) -> f32 {
+    distance_sq(a, b).sqrt()
+}
+
+/// how far butter flies before it drops to the floor
+const BUTTER_RANGE: f32 = 650.0;
+/// how far chain butter will look for its next chef
+const CHAIN_RANGE: f32 = 220.0;
+/// chefs caught in an explosion take this much of the butter's damage
+const EXPLOSION_DAMAGE: f32 = 0.5;
+const GREASE_SLOWDOWN: f32 = 0.5;
+/// how much further a player we can't see counts as when picking who to chase
+const HIDDEN_TARGET_PENALTY: f32 = 300.0;
+/// how much closer another player has to be before we switch to them
+const TARGET_SWITCH_MARGIN: f32 = 150.0;
+
+#[derive(Clone, Debug)]
 pub struct Butter {
     velocity: Vec2<f32>,
     pub pos: Vec2<f32>,
     pub size: Vec2<f32>,
     reflected: bool,
     damage: f32,
+    travelled: f32,
+    effects: ButterEffects,
+    /// chefs we already went through so piercing butter doesn't hit them every frame
+    hit_ids: Vec<u32>,
+    /// chefs this butter has taken out so far
+    kills: u32,
     pub valid: bool,
 }
 
 impl Butter {
-    pub fn new(starting_pos: Vec2<f32>, target: Vec2<f32>) -> Self {
-        let move_towards = move_towards(starting_pos, target, 100.0);
+    pub fn new(starting_pos: Vec2<f32>, target: Vec2<f32>, speed: f32) -> Self {
+        let move_towards = move_towards(starting_pos, target, speed);
         let diff = starting_pos - move_towards;
         Self {
             pos: starting_pos,
@@ -170,49 +527,276 @@ impl Butter {
             velocity: diff,
             reflected: false,
             damage: 30.0,
+            travelled: 0.0,
+            effects: ButterEffects::default(),
+            hit_ids: Vec::new(),
+            kills: 0,
             valid: true,
         }
     }
 
-    pub fn update(&mut self, dt: f32, player: &mut Player, enemies: &mut [Enemy]) {
+    pub fn update(&mut self, dt: f32, players: &mut [Player], enemies: &mut [Enemy], arena: &mut Arena, events: &mut Vec<GameEvent>) {
         let new_x = self.pos.x - (self.velocity.x * dt);
         let new_y = self.pos.y - (self.velocity.y * dt);
 
         self.pos = Vec2{x: new_x, y: new_y};
-        let (p_box_pos, p_box_size) = player.get_hit_box();
 
-        let hit_player = collision::rect_rect(self.size, self.pos, p_box_size, p_box_pos);
+        self.travelled += steering::length(self.velocity) * dt;
+        if self.travelled > BUTTER_RANGE {
+            self.land(arena);
+            return;
+        }
+
+        for obstacle in arena.obstacles.iter() {
+            let Some(push) = collision::resolve_rect(self.size, self.pos, obstacle.size, obstacle.pos) else {
+                continue;
+            };
+
+            if !obstacle.kind.deflects() {
+                self.land(arena);
+                return;
+            }
+
+            // bounce off whichever side we came through
+            self.pos = self.pos + push;
+            if push.x != 0.0 {
+                self.velocity.x = -self.velocity.x;
+            } else {
+                self.velocity.y = -self.velocity.y;
+            }
+        }
+
+        // downed players are lying flat, it sails right over them
+        let hit_player = players.iter_mut().filter(|p| !p.is_dead()).find(|p| {
+            let (p_box_pos, p_box_size) = p.get_hit_box();
+            collision::rect_rect(self.size, self.pos, p_box_size, p_box_pos)
+        });
 
         if self.reflected {
-            enemies
-                .iter_mut()
-                .filter(|e| collision::rect_rect(self.size, self.pos, e.size, e.pos))
-                .for_each(|e| {
-                    e.on_hit(self.damage);
-                    self.valid = false;
-                });
+            self.hit_enemies(enemies, arena, events);
         }
 
-        if hit_player {
-            player.on_hit();
+        if let Some(player) = hit_player {
+            player.on_hit(events);
             self.valid = false;
         }
     }
 
-    pub fn draw(&self, render_handle: &mut RenderInformation, butter_material: &mut Material) {
-        butter_material.add_rectangle(self.pos, self.size, Colour::WHITE, &render_handle);
+    pub fn draw(&self, render_handle: &mut RenderInformation, butter_material: &mut Material, camera: &Camera) {
+        let (pos, size) = camera.rect(self.pos, self.size);
+        butter_material.add_rectangle(pos, size, Colour::WHITE, &render_handle);
         // need to draw it but later.....
     }
 
-    pub fn change_target(&mut self, new_target: Vec2<f32>, charge_time: f32) {
-        let move_towards = move_towards(self.pos, new_target, 100.0);
-        let diff = self.pos - move_towards;
-        self.velocity = diff;
+    /// where it'll be in `seconds` if nothing gets in the way
+    pub fn position_in(&self, seconds: f32) -> Vec2<f32> {
+        Vec2{x: self.pos.x - self.velocity.x * seconds, y: self.pos.y - self.velocity.y * seconds}
+    }
+
+    pub fn change_target(&mut self, new_target: Vec2<f32>, charge_time: f32, damage_multiplier: f32, effects: ButterEffects) {
+        self.aim_at(new_target);
         self.damage += 33.0 * (charge_time + 0.7).log10() + 10.0;
+        self.damage *= damage_multiplier;
+        self.effects = effects;
         self.reflected = true;
     }
 
+    /// a versus volley, off at `target` a bit faster and ready to be parried straight back
+    pub fn volley(&mut self, target: Vec2<f32>, speed_multiplier: f32) {
+        self.velocity = steering::scale(self.velocity, speed_multiplier);
+        self.aim_at(target);
+        self.reflected = false;
+    }
+
+    fn aim_at(&mut self, target: Vec2<f32>) {
+        let speed = steering::length(self.velocity);
+        let move_towards = move_towards(self.pos, target, speed);
+        self.velocity = self.pos - move_towards;
+        self.travelled = 0.0;
+    }
+
+    fn hit_enemies(&mut self, enemies: &mut [Enemy], arena: &mut Arena, events: &mut Vec<GameEvent>) {
+        // only one new chef per frame, otherwise piercing butter eats a whole crowd at once
+        let Some(target) = enemies
+            .iter()
+            .position(|e| e.is_alive() && !self.hit_ids.contains(&e.get_id()) && collision::rect_rect(self.size, self.pos, e.size, e.pos))
+        else {
+            return;
+        };
+
+        let center = self.get_center();
+        self.hit_enemy(&mut enemies[target], self.damage, events);
+        self.hit_ids.push(enemies[target].get_id());
+
+        if self.effects.explosion_radius > 0.0 {
+            let damage = self.damage * EXPLOSION_DAMAGE;
+            let radius = self.effects.explosion_radius;
+            for (_, enemy) in enemies
+                .iter_mut()
+                .enumerate()
+                .filter(|(i, e)| *i != target && e.is_alive() && distance(e.get_center(), center) <= radius)
+            {
+                self.hit_enemy(enemy, damage, events);
+            }
+        }
+
+        if self.effects.grease_time > 0.0 {
+            arena.hazards.add_grease(center, self.effects.grease_time);
+        }
+
+        if self.effects.chains > 0 {
+            let next = enemies
+                .iter()
+                .filter(|e| e.is_alive() && !self.hit_ids.contains(&e.get_id()))
+                .map(|e| (e.get_center(), distance(e.get_center(), center)))
+                .filter(|(_, d)| *d <= CHAIN_RANGE)
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+
+            if let Some((next_center, _)) = next {
+                self.effects.chains -= 1;
+                self.aim_at(next_center);
+                return;
+            }
+        }
+
+        if self.effects.pierce > 0 {
+            self.effects.pierce -= 1;
+            return;
+        }
+
+        self.valid = false;
+    }
+
+    fn hit_enemy(&mut self, enemy: &mut Enemy, damage: f32, events: &mut Vec<GameEvent>) {
+        let pos = enemy.get_center();
+        let killed = enemy.on_hit(damage);
+        events.push(GameEvent::Hit{target: HitTarget::Enemy(enemy.get_id()), pos, damage});
+        if killed {
+            events.push(GameEvent::Kill{pos, cause: KillCause::Butter{nth: self.kills}});
+            self.kills += 1;
+        }
+    }
+
+    /// bends the butter towards `point` without changing its speed
+    pub fn pull_towards(&mut self, point: Vec2<f32>, radius: f32, strength: f32, dt: f32) {
+        let center = self.get_center();
+        let dist = distance(center, point);
+        if dist > radius || dist < 0.001 {
+            return;
+        }
+
+        // velocity is stored backwards, see update
+        let speed = steering::length(self.velocity);
+        let desired = Vec2{x: (center.x - point.x) / dist * speed, y: (center.y - point.y) / dist * speed};
+        let pull = (strength * dt).min(1.0);
+        self.velocity.x += (desired.x - self.velocity.x) * pull;
+        self.velocity.y += (desired.y - self.velocity.y) * pull;
+    }
+
+    fn land(&mut self, arena: &mut Arena) {
+        self.valid = false;
+        arena.hazards.add_puddle(self.get_center());
+        if self.reflected && self.effects.grease_time > 0.0 {
+            arena.hazards.add_grease(self.get_center(), self.effects.grease_time);
+        }
+    }
+
+    pub fn get_center(&self) -> Vec2<f32> {
+        Vec2{x: self.pos.x + self.size.x/2.0, y: self.pos.y + self.size.y/2.0}
+    }
+
+    pub fn speed(&self) -> f32 {
+        steering::length(self.velocity)
+    }
+
     pub fn is_reflected(&self) -> bool {
         self.reflected
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_off(start: Vec2<f32>, bounds: Vec2<f32>) -> bool {
+        let mut enemy = Enemy::new(start, 0, EnemyTuning::DEFAULT);
+        enemy.flee(bounds);
+        (0..2000).any(|_| {
+            enemy.dead_update(1.0 / 60.0, &[], bounds);
+            !enemy.is_valid()
+        })
+    }
+
+    const OPEN_FLOOR: &str = "name Test\nbackground none\nsize 800 800\nspawn_edge left";
+
+    /// the row the chefs stand along, the butter flies through their middles
+    const ROW: f32 = 300.0;
+
+    /// reflected butter heading right along the row of chefs, a 0.3 second charge makes it hit for 40
+    fn reflected_butter(effects: ButterEffects) -> Butter {
+        let mut butter = Butter::new(Vec2{x: 0.0, y: 318.0}, Vec2{x: 800.0, y: 318.0}, 400.0);
+        butter.change_target(Vec2{x: 800.0, y: 318.0}, 0.3, 1.0, effects);
+        butter
+    }
+
+    fn fly(butter: &mut Butter, enemies: &mut [Enemy]) -> Vec<GameEvent> {
+        let mut arena = Arena::parse(OPEN_FLOOR).unwrap();
+        let mut events = Vec::new();
+        for _ in 0..120 {
+            if !butter.valid {
+                break;
+            }
+            butter.update(1.0 / 60.0, &mut [], enemies, &mut arena, &mut events);
+        }
+        events
+    }
+
+    #[test]
+    fn piercing_butter_counts_up_its_kills() {
+        let mut enemies = vec![
+            Enemy::with_hp(Vec2{x: 100.0, y: ROW}, 7, 30.0),
+            Enemy::with_hp(Vec2{x: 250.0, y: ROW}, 8, 30.0),
+            Enemy::with_hp(Vec2{x: 400.0, y: ROW}, 9, 30.0),
+        ];
+        let centers: Vec<Vec2<f32>> = enemies.iter().map(|e| e.get_center()).collect();
+        let mut butter = reflected_butter(ButterEffects{pierce: 2, ..Default::default()});
+
+        let events = fly(&mut butter, &mut enemies);
+
+        let damage = 40.0;
+        assert_eq!(events, vec![
+            GameEvent::Hit{target: HitTarget::Enemy(7), pos: centers[0], damage},
+            GameEvent::Kill{pos: centers[0], cause: KillCause::Butter{nth: 0}},
+            GameEvent::Hit{target: HitTarget::Enemy(8), pos: centers[1], damage},
+            GameEvent::Kill{pos: centers[1], cause: KillCause::Butter{nth: 1}},
+            GameEvent::Hit{target: HitTarget::Enemy(9), pos: centers[2], damage},
+            GameEvent::Kill{pos: centers[2], cause: KillCause::Butter{nth: 2}},
+        ]);
+        assert!(!butter.valid);
+    }
+
+    #[test]
+    fn butter_stops_at_the_first_chef_without_pierce() {
+        let mut enemies = vec![
+            Enemy::with_hp(Vec2{x: 100.0, y: ROW}, 0, 100.0),
+            Enemy::with_hp(Vec2{x: 250.0, y: ROW}, 1, 30.0),
+        ];
+        let mut butter = reflected_butter(ButterEffects::default());
+
+        let events = fly(&mut butter, &mut enemies);
+
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], GameEvent::Hit{target: HitTarget::Enemy(0), ..}));
+        assert!((enemies[0].get_health().unwrap() - 0.6).abs() < 0.001);
+        assert!(!butter.valid);
+    }
+
+    #[test]
+    fn fleeing_chefs_leave_through_every_side() {
+        let bounds = Vec2{x: 800.0, y: 800.0};
+        assert!(run_off(Vec2{x: 20.0, y: 400.0}, bounds));
+        assert!(run_off(Vec2{x: 740.0, y: 400.0}, bounds));
+        assert!(run_off(Vec2{x: 400.0, y: 20.0}, bounds));
+        assert!(run_off(Vec2{x: 400.0, y: 740.0}, bounds));
+    }
+}
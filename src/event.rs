@@ -0,0 +1,42 @@
+use bottomless_pit::vectors::Vec2;
+
+use crate::pickup::PickupKind;
+
+/// Something that happened in the simulation. Whatever causes it pushes one onto the level's
+/// queue and everything that cares (score, stats, sound) reads them back afterwards instead
+/// of being called from deep inside the butter or the player
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameEvent {
+    /// a chef let one fly
+    ButterThrown,
+    /// a player started winding up the pin
+    ParryCharged,
+    /// a player let go with enough charge to actually swing
+    ParrySwung,
+    /// a butter got sent back, perfect when it was about to hit them anyway
+    Reflect{pos: Vec2<f32>, perfect: bool},
+    /// `damage` is in hearts for players and hp for chefs
+    Hit{target: HitTarget, pos: Vec2<f32>, damage: f32},
+    Kill{pos: Vec2<f32>, cause: KillCause},
+    WaveStarted,
+    WaveCleared{wave: u32},
+    /// a player ran out of hearts, in co-op they can still be picked back up
+    PlayerDied{player: usize},
+    /// `pos` is the middle of whoever picked it up
+    PickupCollected{pos: Vec2<f32>, kind: PickupKind},
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HitTarget {
+    /// by slot
+    Player(usize),
+    /// by id
+    Enemy(u32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KillCause {
+    /// `nth` is how many chefs the same butter already took out before this one
+    Butter{nth: u32},
+    Stove,
+}
@@ -0,0 +1,244 @@
+use bottomless_pit::colour::Colour;
+use bottomless_pit::material::Material;
+use bottomless_pit::render::RenderInformation;
+use bottomless_pit::vectors::Vec2;
+
+use crate::collision;
+use crate::camera::Camera;
+use crate::enemy::Enemy;
+use crate::event::{GameEvent, KillCause};
+use crate::player::Player;
+
+// a stove sits cold, glows for a bit as a warning, then burns
+const STOVE_COLD_TIME: f32 = 4.0;
+const STOVE_WARM_TIME: f32 = 1.0;
+const STOVE_HOT_TIME: f32 = 2.0;
+const STOVE_CYCLE: f32 = STOVE_COLD_TIME + STOVE_WARM_TIME + STOVE_HOT_TIME;
+const STOVE_ENEMY_DPS: f32 = 35.0;
+
+const PUDDLE_SIZE: f32 = 40.0;
+const PUDDLE_LIFETIME: f32 = 12.0;
+const MAX_PUDDLES: usize = 30;
+const GREASE_SIZE: f32 = 60.0;
+
+#[derive(Clone, Debug)]
+pub struct Hazards {
+    stoves: Vec<Stovetop>,
+    puddles: Vec<Puddle>,
+    grease: Vec<Puddle>,
+}
+
+impl Hazards {
+    pub fn new(stoves: Vec<Stovetop>) -> Self {
+        Self {
+            stoves,
+            puddles: Vec::new(),
+            grease: Vec::new(),
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.stoves.iter_mut().for_each(|s| s.update(dt));
+        self.puddles.iter_mut().for_each(|p| p.lifetime -= dt);
+        self.puddles.retain(|p| p.lifetime > 0.0);
+        self.grease.iter_mut().for_each(|p| p.lifetime -= dt);
+        self.grease.retain(|p| p.lifetime > 0.0);
+    }
+
+    pub fn reset(&mut self) {
+        self.puddles.clear();
+        self.grease.clear();
+        self.stoves.iter_mut().enumerate().for_each(|(i, s)| s.reset(i));
+    }
+
+    pub fn add_puddle(&mut self, center: Vec2<f32>) {
+        if self.puddles.len() >= MAX_PUDDLES {
+            self.puddles.remove(0);
+        }
+
+        let pos = Vec2{x: center.x - PUDDLE_SIZE / 2.0, y: center.y - PUDDLE_SIZE / 2.0};
+        self.puddles.push(Puddle{pos, lifetime: PUDDLE_LIFETIME});
+    }
+
+    /// grease from perk butter, only slows chefs down
+    pub fn add_grease(&mut self, center: Vec2<f32>, lifetime: f32) {
+        if self.grease.len() >= MAX_PUDDLES {
+            self.grease.remove(0);
+        }
+
+        let pos = Vec2{x: center.x - GREASE_SIZE / 2.0, y: center.y - GREASE_SIZE / 2.0};
+        self.grease.push(Puddle{pos, lifetime});
+    }
+
+    pub fn is_greasy(&self, pos: Vec2<f32>, size: Vec2<f32>) -> bool {
+        let grease_size = Vec2{x: GREASE_SIZE, y: GREASE_SIZE};
+        self.grease.iter().any(|p| collision::rect_rect(size, pos, grease_size, p.pos))
+    }
+
+    pub fn is_slippery(&self, pos: Vec2<f32>, size: Vec2<f32>) -> bool {
+        let puddle_size = Vec2{x: PUDDLE_SIZE, y: PUDDLE_SIZE};
+        self.puddles.iter().any(|p| collision::rect_rect(size, pos, puddle_size, p.pos))
+    }
+
+    /// a hot stove takes one heart per burn, it won't take another until it cools down
+    pub fn burn_players(&mut self, players: &mut [Player], events: &mut Vec<GameEvent>) {
+        for (i, player) in players.iter_mut().enumerate().filter(|(_, p)| !p.is_dead()) {
+            self.burn_player(i, player, events);
+        }
+    }
+
+    fn burn_player(&mut self, index: usize, player: &mut Player, events: &mut Vec<GameEvent>) {
+        let (hit_box_pos, hit_box_size) = player.get_hit_box();
+        for stove in self.stoves.iter_mut() {
+            if stove.is_hot() && !stove.burned_players.contains(&index) && collision::rect_rect(hit_box_size, hit_box_pos, stove.size, stove.pos) {
+                stove.burned_players.push(index);
+                player.on_hit(events);
+            }
+        }
+    }
+
+    pub fn burn_enemies(&self, enemies: &mut [Enemy], dt: f32, events: &mut Vec<GameEvent>) {
+        for stove in self.stoves.iter().filter(|s| s.is_hot()) {
+            enemies
+                .iter_mut()
+                .filter(|e| e.is_alive() && collision::rect_rect(e.size, e.pos, stove.size, stove.pos))
+                .for_each(|e| {
+                    if e.on_hit(STOVE_ENEMY_DPS * dt) {
+                        events.push(GameEvent::Kill{pos: e.get_center(), cause: KillCause::Stove});
+                    }
+                });
+        }
+    }
+
+    pub fn draw(&self, render_handle: &mut RenderInformation, material: &mut Material, camera: &Camera) {
+        for stove in self.stoves.iter() {
+            let (pos, size) = camera.rect(stove.pos, stove.size);
+            material.add_rectangle(pos, size, stove.colour(), render_handle);
+        }
+
+        for puddle in self.puddles.iter() {
+            // fade out over the last couple of seconds
+            let alpha = (puddle.lifetime / 2.0).min(1.0) * 200.0;
+            let colour = Colour::from_rgba(250.0, 225.0, 110.0, alpha);
+            let (pos, size) = camera.rect(puddle.pos, Vec2{x: PUDDLE_SIZE, y: PUDDLE_SIZE});
+            material.add_rectangle(pos, size, colour, render_handle);
+        }
+
+        for grease in self.grease.iter() {
+            let alpha = (grease.lifetime / 2.0).min(1.0) * 180.0;
+            let colour = Colour::from_rgba(120.0, 90.0, 40.0, alpha);
+            let (pos, size) = camera.rect(grease.pos, Vec2{x: GREASE_SIZE, y: GREASE_SIZE});
+            material.add_rectangle(pos, size, colour, render_handle);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Stovetop {
+    pos: Vec2<f32>,
+    size: Vec2<f32>,
+    timer: f32,
+    /// the players this stove already took a heart from this cycle
+    burned_players: Vec<usize>,
+}
+
+impl Stovetop {
+    /// `index` staggers the stoves so they don't all light up at once
+    pub fn new(pos: Vec2<f32>, size: Vec2<f32>, index: usize) -> Self {
+        let mut stove = Self {
+            pos,
+            size,
+            timer: 0.0,
+            burned_players: Vec::new(),
+        };
+        stove.reset(index);
+        stove
+    }
+
+    fn reset(&mut self, index: usize) {
+        self.timer = (index as f32 * 2.3) % STOVE_CYCLE;
+        self.burned_players.clear();
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.timer += dt;
+        if self.timer >= STOVE_CYCLE {
+            self.timer %= STOVE_CYCLE;
+            self.burned_players.clear();
+        }
+    }
+
+    fn is_warm(&self) -> bool {
+        self.timer >= STOVE_COLD_TIME && !self.is_hot()
+    }
+
+    fn is_hot(&self) -> bool {
+        self.timer >= STOVE_COLD_TIME + STOVE_WARM_TIME
+    }
+
+    fn colour(&self) -> Colour {
+        if self.is_hot() {
+            Colour::from_rgba(230.0, 40.0, 20.0, 255.0)
+        } else if self.is_warm() {
+            Colour::from_rgba(240.0, 140.0, 40.0, 255.0)
+        } else {
+            Colour::from_rgba(50.0, 50.0, 55.0, 255.0)
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Puddle {
+    pos: Vec2<f32>,
+    lifetime: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// chefs stand along the top of the stove
+    const ROW: f32 = 100.0;
+
+    #[test]
+    fn stoves_only_burn_when_hot() {
+        let mut hazards = Hazards::new(vec![Stovetop::new(Vec2{x: 0.0, y: 100.0}, Vec2{x: 200.0, y: 60.0}, 0)]);
+        let mut enemies = vec![Enemy::with_hp(Vec2{x: 20.0, y: ROW}, 0, 1.0)];
+        let mut events = Vec::new();
+
+        hazards.update(STOVE_COLD_TIME);
+        hazards.burn_enemies(&mut enemies, 1.0, &mut events);
+        assert!(events.is_empty());
+
+        hazards.update(STOVE_WARM_TIME);
+        hazards.burn_enemies(&mut enemies, 1.0, &mut events);
+        assert_eq!(events.len(), 1);
+    }
+
+    #[test]
+    fn one_kill_per_chef() {
+        let mut hazards = Hazards::new(vec![Stovetop::new(Vec2{x: 0.0, y: 100.0}, Vec2{x: 200.0, y: 60.0}, 0)]);
+        hazards.update(STOVE_COLD_TIME + STOVE_WARM_TIME);
+
+        // two on the stove, the tougher one goes down later, and one standing clear of it
+        let mut enemies = vec![
+            Enemy::with_hp(Vec2{x: 120.0, y: ROW}, 0, 20.0),
+            Enemy::with_hp(Vec2{x: 20.0, y: ROW}, 1, 10.0),
+            Enemy::with_hp(Vec2{x: 400.0, y: ROW}, 2, 10.0),
+        ];
+        let centers: Vec<Vec2<f32>> = enemies.iter().map(|e| e.get_center()).collect();
+        let mut events = Vec::new();
+
+        let dt = 1.0 / 60.0;
+        for _ in 0..90 {
+            hazards.update(dt);
+            hazards.burn_enemies(&mut enemies, dt, &mut events);
+        }
+
+        assert_eq!(events, vec![
+            GameEvent::Kill{pos: centers[1], cause: KillCause::Stove},
+            GameEvent::Kill{pos: centers[0], cause: KillCause::Stove},
+        ]);
+        assert!(enemies[2].is_alive());
+    }
+}
@@ -0,0 +1,204 @@
+use std::fs;
+
+use crate::difficulty::Difficulty;
+use crate::mode::GameMode;
+
+const SAVE_PATH: &str = "highscores.txt";
+const DAILY_SAVE_PATH: &str = "daily_scores.txt";
+
+/// The best run for one arena, difficulty and mode. Saved one record per line as `key=value` pairs
+/// split by `;` so new fields can be added without breaking old saves.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    pub arena: String,
+    pub difficulty: Difficulty,
+    pub mode: GameMode,
+    pub wave: u32,
+    pub kills: u32,
+    pub score: u32,
+}
+
+impl Record {
+    fn is_better_than(&self, other: &Self) -> bool {
+        match self.mode {
+            // the clock is the same for everyone so it's all about the chefs
+            GameMode::TimeAttack => (self.kills, self.score) > (other.kills, other.score),
+            _ => (self.score, self.wave, self.kills) > (other.score, other.wave, other.kills),
+        }
+    }
+
+    fn is_same_run(&self, arena: &str, difficulty: Difficulty, mode: GameMode) -> bool {
+        self.arena == arena && self.difficulty == difficulty && self.mode == mode
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let mut arena = None;
+        // saves from before difficulties were all played on normal
+        let mut difficulty = Difficulty::Normal;
+        // and before modes everything was endless
+        let mut mode = GameMode::Endless;
+        let mut wave = None;
+        let mut kills = None;
+        // saves from before scores existed don't have one
+        let mut score = 0;
+
+        for pair in line.split(';') {
+            let (key, value) = pair.split_once('=')?;
+            match key {
+                "arena" => arena = Some(value.to_string()),
+                "difficulty" => difficulty = Difficulty::from_key(value)?,
+                "mode" => mode = GameMode::from_key(value)?,
+                "wave" => wave = value.parse().ok(),
+                "kills" => kills = value.parse().ok(),
+                "score" => score = value.parse().ok()?,
+                // fields from a newer version, just skip them
+                _ => {},
+            }
+        }
+
+        Some(Self {
+            arena: arena?,
+            difficulty,
+            mode,
+            wave: wave?,
+            kills: kills?,
+            score,
+        })
+    }
+
+    fn to_line(&self) -> String {
+        format!(
+            "arena={};difficulty={};mode={};wave={};kills={};score={}",
+            self.arena, self.difficulty.key(), self.mode.key(), self.wave, self.kills, self.score,
+        )
+    }
+}
+
+pub struct HighScores {
+    records: Vec<Record>,
+}
+
+impl HighScores {
+    /// a missing or broken save just means no high scores yet
+    pub fn load() -> Self {
+        let records = fs::read_to_string(SAVE_PATH)
+            .map(|s| s.lines().filter_map(Record::parse).collect())
+            .unwrap_or_default();
+
+        Self {
+            records,
+        }
+    }
+
+    pub fn save(&self) {
+        let contents: Vec<String> = self.records.iter().map(|r| r.to_line()).collect();
+        // nowhere to save on the web, losing the scores isn't worth crashing over
+        let _ = fs::write(SAVE_PATH, contents.join("\n"));
+    }
+
+    pub fn best(&self, arena: &str, difficulty: Difficulty, mode: GameMode) -> Option<&Record> {
+        self.records.iter().find(|r| r.is_same_run(arena, difficulty, mode))
+    }
+
+    /// keeps the record if it beats the best for its arena, difficulty and mode, returns true when it did
+    pub fn submit(&mut self, record: Record) -> bool {
+        match self.records.iter_mut().find(|r| r.is_same_run(&record.arena, record.difficulty, record.mode)) {
+            Some(best) if record.is_better_than(best) => *best = record,
+            Some(_) => return false,
+            None => self.records.push(record),
+        }
+
+        self.save();
+        true
+    }
+}
+
+/// The best attempt at one day's challenge, saved in the same `key=value` format as [`Record`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DailyRecord {
+    /// `YYYY-MM-DD`, sorts the same way the days do
+    pub date: String,
+    pub wave: u32,
+    pub kills: u32,
+    pub score: u32,
+}
+
+impl DailyRecord {
+    fn is_better_than(&self, other: &Self) -> bool {
+        (self.score, self.wave, self.kills) > (other.score, other.wave, other.kills)
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let mut date = None;
+        let mut wave = None;
+        let mut kills = None;
+        let mut score = None;
+
+        for pair in line.split(';') {
+            let (key, value) = pair.split_once('=')?;
+            match key {
+                "date" => date = Some(value.to_string()),
+                "wave" => wave = value.parse().ok(),
+                "kills" => kills = value.parse().ok(),
+                "score" => score = value.parse().ok(),
+                _ => {},
+            }
+        }
+
+        Some(Self {
+            date: date?,
+            wave: wave?,
+            kills: kills?,
+            score: score?,
+        })
+    }
+
+    fn to_line(&self) -> String {
+        format!("date={};wave={};kills={};score={}", self.date, self.wave, self.kills, self.score)
+    }
+}
+
+pub struct DailyScores {
+    records: Vec<DailyRecord>,
+}
+
+impl DailyScores {
+    pub fn load() -> Self {
+        let records = fs::read_to_string(DAILY_SAVE_PATH)
+            .map(|s| s.lines().filter_map(DailyRecord::parse).collect())
+            .unwrap_or_default();
+
+        Self {
+            records,
+        }
+    }
+
+    pub fn save(&self) {
+        let contents: Vec<String> = self.records.iter().map(|r| r.to_line()).collect();
+        let _ = fs::write(DAILY_SAVE_PATH, contents.join("\n"));
+    }
+
+    pub fn best(&self, date: &str) -> Option<&DailyRecord> {
+        self.records.iter().find(|r| r.date == date)
+    }
+
+    /// the last few days played, newest first
+    pub fn recent(&self, count: usize) -> Vec<&DailyRecord> {
+        let mut records: Vec<&DailyRecord> = self.records.iter().collect();
+        records.sort_by(|a, b| b.date.cmp(&a.date));
+        records.truncate(count);
+        records
+    }
+
+    /// keeps the record if it beats that day's best, returns true when it did
+    pub fn submit(&mut self, record: DailyRecord) -> bool {
+        match self.records.iter_mut().find(|r| r.date == record.date) {
+            Some(best) if record.is_better_than(best) => *best = record,
+            Some(_) => return false,
+            None => self.records.push(record),
+        }
+
+        self.save();
+        true
+    }
+}
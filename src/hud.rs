@@ -0,0 +1,228 @@
+use bottomless_pit::colour::Colour;
+use bottomless_pit::engine_handle::Engine;
+use bottomless_pit::material::{Material, MaterialBuilder};
+use bottomless_pit::render::RenderInformation;
+use bottomless_pit::vectors::Vec2;
+
+use crate::mode;
+use crate::pickup::PickupKind;
+use crate::player::{HEART_ROW_HEIGHT, MIN_SWING_CHARGE};
+use crate::text::Text;
+use crate::viewport::{Viewport, LOGICAL_SIZE};
+
+/// a full charge meter, winding up for longer still helps but only a little
+const CHARGE_METER_FULL: f32 = 2.0;
+const CHARGE_METER_SIZE: Vec2<f32> = Vec2{x: 200.0, y: 14.0};
+const WAVE_BAR_SIZE: Vec2<f32> = Vec2{x: 160.0, y: 10.0};
+const EFFECT_ROW_HEIGHT: f32 = 35.0;
+
+/// Which corner or edge of the screen something is laid out from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Anchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    BottomCenter,
+}
+
+impl Anchor {
+    /// The top left corner for something `size` big. `offset` pushes it in from the
+    /// anchored edges, and along to the right when it's centred
+    pub fn place(&self, size: Vec2<f32>, offset: Vec2<f32>) -> Vec2<f32> {
+        let x = match self {
+            Self::TopLeft => offset.x,
+            Self::TopCenter | Self::BottomCenter => (LOGICAL_SIZE.x - size.x) / 2.0 + offset.x,
+            Self::TopRight => LOGICAL_SIZE.x - size.x - offset.x,
+        };
+        let y = match self {
+            Self::TopLeft | Self::TopCenter | Self::TopRight => offset.y,
+            Self::BottomCenter => LOGICAL_SIZE.y - size.y - offset.y,
+        };
+        Vec2{x, y}
+    }
+}
+
+/// text that puts itself back in place whenever what it says changes size
+struct HudText {
+    text: Text,
+    anchor: Anchor,
+    offset: Vec2<f32>,
+}
+
+impl HudText {
+    fn new(text: &str, font_size: f32, anchor: Anchor, offset: Vec2<f32>, engine_handle: &mut Engine) -> Self {
+        let mut hud_text = Self {
+            text: Text::new(text, font_size, Vec2{x: 0.0, y: 0.0}, Colour::BLACK, engine_handle),
+            anchor,
+            offset,
+        };
+        hud_text.place();
+        hud_text
+    }
+
+    fn set(&mut self, text: &str, engine_handle: &mut Engine) {
+        self.text.change_text(text, engine_handle);
+        self.place();
+    }
+
+    fn place(&mut self) {
+        let size = Vec2{x: self.text.size.x as f32, y: self.text.size.y as f32};
+        self.text.pos = self.anchor.place(size, self.offset);
+    }
+}
+
+/// Everything the HUD shows, the level fills one in and the HUD works out what changed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HudStats {
+    pub score: u32,
+    /// in tenths so it only changes when the shown number does
+    pub combo: u32,
+    pub wave: u32,
+    pub wave_size: u32,
+    /// still to spawn plus still standing
+    pub chefs_left: u32,
+    pub kills: u32,
+    pub coins: u32,
+    /// whole seconds, only in modes with a time limit
+    pub time_left: Option<u32>,
+}
+
+/// what the HUD needs to know about each player
+pub struct PlayerHud {
+    pub effects: Vec<(PickupKind, f32)>,
+    /// None when they aren't winding up
+    pub charge: Option<f32>,
+}
+
+/// The score, wave and run info laid out around the edge of the screen. The hearts still
+/// come from the players themselves since versus shows them too
+pub struct Hud {
+    score: HudText,
+    combo: HudText,
+    time: HudText,
+    wave: HudText,
+    chefs_left: HudText,
+    coins: HudText,
+    kills: HudText,
+    /// None until the first update so everything gets written out then
+    shown: Option<HudStats>,
+    bars: Material,
+    effect_icons: [Material; 4],
+}
+
+impl Hud {
+    pub fn new(engine_handle: &mut Engine) -> Self {
+        Self {
+            score: HudText::new("Score: 0", 40.0, Anchor::TopCenter, Vec2{x: 0.0, y: 0.0}, engine_handle),
+            combo: HudText::new("x1.0", 30.0, Anchor::TopCenter, Vec2{x: 0.0, y: 45.0}, engine_handle),
+            time: HudText::new("", 35.0, Anchor::TopCenter, Vec2{x: 0.0, y: 80.0}, engine_handle),
+            wave: HudText::new("Wave: 1", 40.0, Anchor::TopRight, Vec2{x: 5.0, y: 0.0}, engine_handle),
+            chefs_left: HudText::new("Chefs left: 0", 25.0, Anchor::TopRight, Vec2{x: 5.0, y: 62.0}, engine_handle),
+            coins: HudText::new("Coins: 0", 30.0, Anchor::TopRight, Vec2{x: 5.0, y: 92.0}, engine_handle),
+            kills: HudText::new("Kills: 0", 30.0, Anchor::TopRight, Vec2{x: 5.0, y: 127.0}, engine_handle),
+            shown: None,
+            bars: MaterialBuilder::new().build(engine_handle),
+            effect_icons: PickupKind::create_materials(engine_handle),
+        }
+    }
+
+    /// only rewrites the text that actually changed
+    pub fn update(&mut self, stats: HudStats, engine_handle: &mut Engine) {
+        let shown = self.shown;
+        let changed = |field: fn(&HudStats) -> u32| shown.map(|s| field(&s)) != Some(field(&stats));
+
+        if changed(|s| s.score) {
+            self.score.set(&format!("Score: {}", stats.score), engine_handle);
+        }
+        if changed(|s| s.combo) {
+            self.combo.set(&format!("x{:.1}", stats.combo as f32 / 10.0), engine_handle);
+        }
+        if changed(|s| s.wave) {
+            self.wave.set(&format!("Wave: {}", stats.wave), engine_handle);
+        }
+        if changed(|s| s.chefs_left) {
+            self.chefs_left.set(&format!("Chefs left: {}", stats.chefs_left), engine_handle);
+        }
+        if changed(|s| s.coins) {
+            self.coins.set(&format!("Coins: {}", stats.coins), engine_handle);
+        }
+        if changed(|s| s.kills) {
+            self.kills.set(&format!("Kills: {}", stats.kills), engine_handle);
+        }
+        if shown.map(|s| s.time_left) != Some(stats.time_left) {
+            if let Some(time_left) = stats.time_left {
+                self.time.set(&format!("Time: {}", mode::format_time(time_left as f32)), engine_handle);
+            }
+        }
+
+        self.shown = Some(stats);
+    }
+
+    /// call whenever the window might have changed size
+    pub fn fit(&mut self, viewport: &Viewport, engine_handle: &mut Engine) {
+        for text in self.texts_mut() {
+            text.text.fit(viewport, engine_handle);
+            text.place();
+        }
+    }
+
+    pub fn draw<'p, 'o>(&'o mut self, render_handle: &mut RenderInformation<'p, 'o>, viewport: &Viewport, players: &[PlayerHud]) where 'o: 'p {
+        if let Some(stats) = self.shown {
+            // how much of the wave has been cleared, under the wave number
+            let done = 1.0 - stats.chefs_left as f32 / stats.wave_size.max(1) as f32;
+            let bar_pos = Anchor::TopRight.place(WAVE_BAR_SIZE, Vec2{x: 5.0, y: 48.0});
+            self.add_bar(render_handle, viewport, bar_pos, WAVE_BAR_SIZE, done.clamp(0.0, 1.0), Colour::GREEN);
+        }
+
+        // a little icon and a shrinking bar under the hearts for every running effect
+        let effects_top = players.len() as f32 * HEART_ROW_HEIGHT + 5.0;
+        let effects = players.iter().flat_map(|p| p.effects.iter());
+        for (i, (kind, left)) in effects.enumerate() {
+            let offset = Vec2{x: 5.0, y: effects_top + i as f32 * EFFECT_ROW_HEIGHT};
+            let (icon_pos, icon_size) = viewport.rect(Anchor::TopLeft.place(Vec2{x: 30.0, y: 30.0}, offset), Vec2{x: 30.0, y: 30.0});
+            self.effect_icons[kind.index()].add_rectangle(icon_pos, icon_size, Colour::WHITE, render_handle);
+
+            let bar_size = Vec2{x: 120.0, y: 10.0};
+            let bar_pos = Anchor::TopLeft.place(bar_size, Vec2{x: offset.x + 35.0, y: offset.y + 10.0});
+            self.add_bar(render_handle, viewport, bar_pos, bar_size, *left, Colour::YELLOW);
+        }
+
+        // one charge meter per player along the bottom, with a notch where a swing starts counting
+        for (slot, player) in players.iter().enumerate() {
+            let pos = Anchor::BottomCenter.place(CHARGE_METER_SIZE, Vec2{x: 0.0, y: 15.0 + slot as f32 * 24.0});
+            let charge = player.charge.unwrap_or(0.0);
+            let colour = if charge >= MIN_SWING_CHARGE {
+                Colour::ORANGE
+            } else {
+                Colour::from_rgba(160.0, 160.0, 160.0, 255.0)
+            };
+            self.add_bar(render_handle, viewport, pos, CHARGE_METER_SIZE, (charge / CHARGE_METER_FULL).min(1.0), colour);
+
+            let notch_x = pos.x + CHARGE_METER_SIZE.x * MIN_SWING_CHARGE / CHARGE_METER_FULL;
+            let (notch_pos, notch_size) = viewport.rect(Vec2{x: notch_x, y: pos.y}, Vec2{x: 2.0, y: CHARGE_METER_SIZE.y});
+            self.bars.add_rectangle(notch_pos, notch_size, Colour::WHITE, render_handle);
+        }
+
+        self.effect_icons.iter_mut().for_each(|m| m.draw(render_handle));
+        self.bars.draw(render_handle);
+
+        for text in [&mut self.score, &mut self.combo, &mut self.wave, &mut self.chefs_left, &mut self.coins, &mut self.kills] {
+            text.text.draw(render_handle, viewport);
+        }
+        // the clock only shows up when there's a time limit
+        if self.shown.is_some_and(|s| s.time_left.is_some()) {
+            self.time.text.draw(render_handle, viewport);
+        }
+    }
+
+    fn add_bar(&mut self, render_handle: &RenderInformation, viewport: &Viewport, pos: Vec2<f32>, size: Vec2<f32>, fill: f32, colour: Colour) {
+        let (back_pos, back_size) = viewport.rect(pos, size);
+        let (_, fill_size) = viewport.rect(pos, Vec2{x: size.x * fill, y: size.y});
+        self.bars.add_rectangle(back_pos, back_size, Colour::BLACK, render_handle);
+        self.bars.add_rectangle(back_pos, fill_size, colour, render_handle);
+    }
+
+    fn texts_mut(&mut self) -> [&mut HudText; 7] {
+        [&mut self.score, &mut self.combo, &mut self.wave, &mut self.chefs_left, &mut self.coins, &mut self.kills, &mut self.time]
+    }
+}
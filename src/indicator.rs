@@ -0,0 +1,86 @@
+use std::f32::consts::PI;
+
+use bottomless_pit::colour::Colour;
+use bottomless_pit::engine_handle::Engine;
+use bottomless_pit::material::{Material, MaterialBuilder};
+use bottomless_pit::render::RenderInformation;
+use bottomless_pit::texture::Texture;
+use bottomless_pit::vectors::Vec2;
+
+use crate::camera::Camera;
+use crate::enemy::{Butter, Enemy};
+use crate::viewport::{Viewport, LOGICAL_SIZE};
+
+/// how far in from the edge of the screen the arrows sit
+const MARGIN: f32 = 24.0;
+const ARROW_SIZE: f32 = 28.0;
+/// butter gets a warning once it's this many seconds away from flying on screen
+const BUTTER_WARNING: f32 = 0.6;
+/// how finely we step along the butter's path looking for where it comes on screen
+const BUTTER_WARNING_STEP: f32 = 0.05;
+
+/// An arrow on the edge of the screen pointing at something the camera can't see,
+/// red behind it for chefs and yellow for butter that's about to fly in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Indicator {
+    /// the middle of the arrow on the logical screen
+    pub pos: Vec2<f32>,
+    /// in degrees, the way the engine wants them
+    pub rotation: f32,
+    pub colour: Colour,
+}
+
+impl Indicator {
+    pub fn create_material(engine_handle: &mut Engine) -> Material {
+        let texture = Texture::new(engine_handle, "assets/arrow.png");
+        MaterialBuilder::new().add_texture(texture).build(engine_handle)
+    }
+
+    /// None when `target` is already on screen
+    pub fn pointing_at(target: Vec2<f32>, camera: &Camera, colour: Colour) -> Option<Self> {
+        let pos = camera.edge_point(target, MARGIN)?;
+        let angle = (pos.y - LOGICAL_SIZE.y / 2.0).atan2(pos.x - LOGICAL_SIZE.x / 2.0) / PI * 180.0;
+
+        Some(Self {
+            pos,
+            rotation: (360.0 - angle) % 360.0,
+            colour,
+        })
+    }
+
+    pub fn for_chef(enemy: &Enemy, camera: &Camera) -> Option<Self> {
+        if !enemy.is_alive() {
+            return None;
+        }
+        Self::pointing_at(enemy.get_center(), camera, Colour::from_rgba(220.0, 60.0, 60.0, 200.0))
+    }
+
+    /// only for chef butter that's going to come on screen soon, it fades in the closer it gets
+    pub fn for_butter(butter: &Butter, camera: &Camera) -> Option<Self> {
+        let center = butter.get_center();
+        if butter.is_reflected() || camera.is_on_screen(center) {
+            return None;
+        }
+
+        let offset = center - butter.pos;
+        let steps = (BUTTER_WARNING / BUTTER_WARNING_STEP) as u32;
+        let arrives = (1..=steps)
+            .map(|step| step as f32 * BUTTER_WARNING_STEP)
+            .find(|seconds| camera.is_on_screen(butter.position_in(*seconds) + offset))?;
+
+        let closeness = 1.0 - arrives / BUTTER_WARNING;
+        let colour = Colour::from_rgba(255.0, 220.0, 60.0, 120.0 + 135.0 * closeness);
+        Self::pointing_at(center, camera, colour)
+    }
+
+    /// the coloured square goes on `backing`, the arrow over it on `arrow`
+    pub fn draw(&self, render_handle: &mut RenderInformation, backing: &mut Material, arrow: &mut Material, viewport: &Viewport) {
+        let half = ARROW_SIZE / 2.0;
+        let (pos, size) = viewport.rect(
+            Vec2{x: self.pos.x - half, y: self.pos.y - half},
+            Vec2{x: ARROW_SIZE, y: ARROW_SIZE},
+        );
+        backing.add_rectangle(pos, size, self.colour, render_handle);
+        arrow.add_rectangle_with_rotation(pos, size, Colour::WHITE, self.rotation, render_handle);
+    }
+}
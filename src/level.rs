@@ -4,170 +4,817 @@ use bottomless_pit::material::{Material, MaterialBuilder};
 use bottomless_pit::render::RenderInformation;
 use bottomless_pit::texture::Texture;
 use bottomless_pit::vectors::Vec2;
-use rand::Rng;
-use rand::rngs::ThreadRng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
 use crate::animation::Anmiation;
+use crate::arena::Arena;
+use crate::camera::Camera;
 use crate::enemy::{Butter, Enemy};
-use crate::player::Player;
-use crate::text::Text;
+use crate::hazard::Hazards;
+use crate::player::{Controls, Player, PlayerInput, PlayerSprites};
+use crate::collision;
+use crate::daily::{DailyChallenge, DailyModifier};
+use crate::damage_number::DamageNumbers;
+use crate::difficulty::Difficulty;
+use crate::event::{GameEvent, HitTarget, KillCause};
+use crate::hud::{Hud, HudStats, PlayerHud};
+use crate::indicator::Indicator;
+use crate::mode::GameMode;
+use crate::particle::Particles;
+use crate::enemy::EnemyTuning;
+use crate::perk::Perk;
+use crate::pickup::{Pickup, PickupKind};
+use crate::score::Score;
+use crate::upgrade::Upgrade;
+use crate::viewport::Viewport;
+
+const COINS_PER_KILL: u32 = 10;
+const HEAL_COST: u32 = 15;
+const PICKUP_DROP_CHANCE: f64 = 0.2;
+/// how close a partner has to stand to pick a downed player back up
+const REVIVE_RADIUS: f32 = 90.0;
 
 pub struct Level {
-    player: Player,
+    /// player one is always first, co-op adds player two after them
+    players: Vec<Player>,
     enemies: Vec<Enemy>,
     butters: Vec<Butter>,
-    text: Vec<Text>,
-    wave_number: u32,
-    enemies_spawned: u32,
+    pickups: Vec<Pickup>,
+    arena: Arena,
+    background: Material,
+    obstacle_material: Material,
+    wave: Wave,
     spawn_timer: f32,
     butter_texture: Material,
+    pickup_materials: [Material; 4],
+    hud_material: Material,
+    hud: Hud,
+    arrow_material: Material,
+    letterbox_material: Material,
+    particles: Particles,
+    particle_material: Material,
+    damage_numbers: DamageNumbers,
+    camera: Camera,
     enemy_animations: [Anmiation; 4],
-    random: ThreadRng,
+    player_sprites: PlayerSprites,
+    random: ChaCha8Rng,
     total_kills: u32,
+    score: Score,
+    /// earned per kill and spent in the shop between waves
+    coins: u32,
+    next_enemy_id: u32,
+    difficulty: Difficulty,
+    mode: GameMode,
+    /// only counts down in modes with a time limit
+    time_left: f32,
+    daily: Option<DailyChallenge>,
+    /// replays the same chefs and drops on every restart when set
+    seed: Option<u64>,
+    /// everything that happened since the game last asked, the level reads through each
+    /// step's share itself before anyone else sees them
+    events: Vec<GameEvent>,
 }
 
 impl Level {
-    pub fn new(engine_handle: &mut Engine) -> Self {
-        let player = Player::new(Vec2{x: 400.0, y: 400.0}, engine_handle);
+    pub fn new(arena: Arena, engine_handle: &mut Engine) -> Self {
+        let player = Player::new(Self::player_spawns(&arena, 1)[0], 0, Controls::Mouse);
 
         let butter_tex = Texture::new(engine_handle, "assets/butter.png");
         let butter_texture = MaterialBuilder::new().add_texture(butter_tex).build(engine_handle);
 
-        let mut wave_text = Text::new("Wave: 1", 40.0, Vec2{x: 200.0, y: 0.0}, Colour::BLACK, engine_handle);
-        let size = wave_text.size;
-        let x = 800 - size.x;
-        wave_text.pos.x = x as f32;
-
         let enemy_animations = Enemy::create_animations(engine_handle);
+        let background = Self::create_background(&arena, engine_handle);
 
         Self {
-            player,
+            players: vec![player],
             enemies: Vec::new(),
-            text: vec![wave_text],
+            hud: Hud::new(engine_handle),
+            arena,
+            background,
+            obstacle_material: MaterialBuilder::new().build(engine_handle),
             butters: Vec::new(),
-            wave_number: 1,
-            enemies_spawned: 0,
+            pickups: Vec::new(),
+            wave: Wave::new(1),
             spawn_timer: 0.0,
             butter_texture,
+            pickup_materials: PickupKind::create_materials(engine_handle),
+            hud_material: MaterialBuilder::new().build(engine_handle),
+            arrow_material: Indicator::create_material(engine_handle),
+            letterbox_material: MaterialBuilder::new().build(engine_handle),
+            particles: Particles::new(),
+            particle_material: MaterialBuilder::new().build(engine_handle),
+            damage_numbers: DamageNumbers::new(),
+            camera: Camera::new(),
             enemy_animations,
-            random: rand::thread_rng(),
+            player_sprites: PlayerSprites::new(engine_handle),
+            random: ChaCha8Rng::from_entropy(),
             total_kills: 0,
+            score: Score::new(),
+            coins: 0,
+            next_enemy_id: 0,
+            difficulty: Difficulty::Normal,
+            mode: GameMode::Endless,
+            time_left: 0.0,
+            daily: None,
+            seed: None,
+            events: Vec::new(),
         }
     }
 
     pub fn update(&mut self, engine_handle: &mut Engine, dt: f32) {
-        self.spawn_enemy(dt);
+        self.update_camera(dt);
+        // hit-stop, everything holds still for a moment after a big hit
+        if self.camera.is_frozen() {
+            return;
+        }
+
+        let inputs: Vec<PlayerInput> = self.players.iter().map(|p| self.aim_in_world(p.read_input(engine_handle))).collect();
+        self.step(&inputs, engine_handle, dt);
+    }
+
+    /// the camera runs on real time, online games step the level by hand and call this themselves.
+    /// They never hit-stop since both sides have to keep stepping together
+    pub fn update_camera(&mut self, dt: f32) {
+        self.camera.follow(self.follow_target(), self.arena.size, dt);
+        self.camera.update(dt);
+    }
+
+    /// the middle of everyone still standing, or of everyone once they're all down
+    fn follow_target(&self) -> Vec2<f32> {
+        let standing: Vec<Vec2<f32>> = self.players.iter().filter(|p| !p.is_dead()).map(|p| p.get_center()).collect();
+        let centers = if standing.is_empty() {
+            self.players.iter().map(|p| p.get_center()).collect()
+        } else {
+            standing
+        };
+
+        let total = centers.iter().fold(Vec2{x: 0.0, y: 0.0}, |total, c| total + *c);
+        let count = centers.len().max(1) as f32;
+        Vec2{x: total.x / count, y: total.y / count}
+    }
 
-        self.player.update(engine_handle, dt, &mut self.butters);
+    /// one update with the inputs already decided, one per player. Given the same seed and
+    /// the same inputs and `dt`s every machine ends up in the same place
+    pub fn step(&mut self, inputs: &[PlayerInput], engine_handle: &mut Engine, dt: f32) {
+        let first_event = self.events.len();
+        self.simulate(inputs, dt);
 
         self.enemy_animations.iter_mut().for_each(|a| a.update(dt));
-        self.enemies.iter_mut().for_each(|e| e.update(dt, &mut self.player, &mut self.butters, &mut self.random));
+        self.particles.handle_events(&self.events[first_event..]);
+        self.particles.update(dt);
+        self.damage_numbers.handle_events(&self.events[first_event..], engine_handle);
+        self.damage_numbers.update(dt);
+        self.camera.handle_events(&self.events[first_event..]);
+        self.update_hud(engine_handle);
+    }
+
+    /// Plays a tick again after an online game rewinds. What it throws up was already seen and
+    /// heard the first time round so the events get dropped, the next normal step puts the HUD right
+    pub fn resimulate(&mut self, inputs: &[PlayerInput], dt: f32) {
+        let first_event = self.events.len();
+        self.simulate(inputs, dt);
+        self.events.truncate(first_event);
+    }
 
-        self.butters.iter_mut().for_each(|s| s.update(dt, &mut self.player, &mut self.enemies));
-        self.butters.retain(|b| b.valid && (b.pos.x > 0.0 && b.pos.x < 800.0) && (b.pos.y > 0.0 && b.pos.y < 800.0));
+    /// everything a step does that decides how the game plays out, none of the looks
+    fn simulate(&mut self, inputs: &[PlayerInput], dt: f32) {
+        let first_event = self.events.len();
+        self.spawn_enemy(dt);
+        self.score.update(dt);
+        self.update_clock(dt);
+
+        for (player, input) in self.players.iter_mut().zip(inputs) {
+            player.update(input, dt, &mut self.butters, &self.arena, &mut self.events);
+        }
 
-        let len_b4 = self.enemies.len() as u32;
+        // chefs that have been taken out are lying on the floor, nobody has to walk around them
+        let centers: Vec<(usize, u32, Vec2<f32>)> = self.enemies
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.is_alive())
+            .map(|(i, e)| (i, e.get_id(), e.get_center()))
+            .collect();
+        let butters_before = self.butters.len();
+        for (i, enemy) in self.enemies.iter_mut().enumerate() {
+            let neighbours: Vec<(u32, Vec2<f32>)> = centers
+                .iter()
+                .filter(|(j, _, _)| *j != i)
+                .map(|(_, id, c)| (*id, *c))
+                .collect();
+            enemy.update(dt, &self.players, &neighbours, &self.arena, &mut self.butters, &mut self.random);
+        }
+        if self.butters.len() > butters_before {
+            self.events.push(GameEvent::ButterThrown);
+        }
+
+        self.butters.iter_mut().for_each(|s| s.update(dt, &mut self.players, &mut self.enemies, &mut self.arena, &mut self.events));
+        self.update_hazards(dt);
+        self.butters.retain(|b| b.valid && self.arena.contains(b.pos));
         self.enemies.retain(|e| e.is_valid());
-        let len_after = self.enemies.len() as u32;
+        self.update_pickups(dt);
+        self.update_revives(dt);
 
-        self.total_kills += len_b4 - len_after;
+        if let Some(cleared) = self.wave.clear(self.enemies.len()) {
+            self.events.push(cleared);
+        }
+        self.handle_events(first_event);
 
-        if self.player.is_dead() {
+        if self.is_run_over() {
             // set target to closet edge
-            self.enemies.iter_mut().for_each(|e| e.walk_off());
+            let bounds = self.arena.size;
+            self.enemies.iter_mut().for_each(|e| e.flee(bounds));
         }
+    }
 
-        if self.is_wave_over() {
-            self.set_wave(self.wave_number + 1, engine_handle);
+    /// score, coins, kills and drops all come from what happened during the step
+    fn handle_events(&mut self, first: usize) {
+        let events = self.events[first..].to_vec();
+        for event in events {
+            match event {
+                GameEvent::Reflect{perfect, ..} => self.score.reflect(perfect),
+                GameEvent::Hit{target: HitTarget::Player(_), ..} => self.score.player_hit(),
+                GameEvent::Kill{pos, cause} => {
+                    self.total_kills += 1;
+                    self.coins += COINS_PER_KILL;
+                    if let KillCause::Butter{nth} = cause {
+                        self.score.reflect_kill(nth);
+                    }
+                    self.drop_pickup(pos);
+                },
+                GameEvent::WaveCleared{wave} => self.score.wave_cleared(wave),
+                _ => {},
+            }
         }
     }
 
     pub fn dead_update(&mut self, engine_handle: &mut Engine, dt: f32) {
-        self.player.update(engine_handle, dt, &mut self.butters);
+        let first_event = self.events.len();
+        for player in self.players.iter_mut() {
+            let mut input = player.read_input(engine_handle);
+            input.aim = input.aim.map(|aim| self.camera.to_world(aim));
+            player.update(&input, dt, &mut self.butters, &self.arena, &mut self.events);
+        }
 
         self.enemy_animations.iter_mut().for_each(|a| a.update(dt));
-        self.butters.iter_mut().for_each(|s| s.update(dt, &mut self.player, &mut self.enemies));
-        self.enemies.iter_mut().for_each(|e| e.dead_update(dt, &self.player));
+        self.butters.iter_mut().for_each(|s| s.update(dt, &mut self.players, &mut self.enemies, &mut self.arena, &mut self.events));
+        self.arena.hazards.update(dt);
+        self.enemies.iter_mut().for_each(|e| e.dead_update(dt, &self.players, self.arena.size));
         self.enemies.retain(|e| e.is_valid());
-        self.butters.retain(|b| b.valid && (b.pos.x > 0.0 && b.pos.x < 800.0) && (b.pos.y > 0.0 && b.pos.y < 800.0));
+        self.butters.retain(|b| b.valid && self.arena.contains(b.pos));
+        self.particles.handle_events(&self.events[first_event..]);
+        self.particles.update(dt);
+        self.damage_numbers.handle_events(&self.events[first_event..], engine_handle);
+        self.damage_numbers.update(dt);
+        self.camera.handle_events(&self.events[first_event..]);
+        self.update_camera(dt);
     }
 
     pub fn draw<'p, 'o>(&'o mut self, render_handle: &mut RenderInformation<'p, 'o>) where 'o: 'p {
+        // grab these before drawing borrows the players for the rest of the frame
+        let player_huds: Vec<PlayerHud> = self.players
+            .iter()
+            .map(|p| PlayerHud{effects: p.active_effects(), charge: p.get_charge()})
+            .collect();
+        let bodies: Vec<(Vec2<f32>, Vec2<f32>)> = self.players.iter().map(|p| p.get_body()).collect();
+        let revives: Vec<(Vec2<f32>, f32)> = self.players
+            .iter()
+            .filter(|p| p.is_dead() && p.revive_progress() > 0.0)
+            .map(|p| (p.pos, p.revive_progress()))
+            .collect();
+        let health_bars: Vec<(Vec2<f32>, f32, f32)> = self.enemies
+            .iter()
+            .filter_map(|e| e.get_health().map(|health| (e.pos, e.size.x, health)))
+            .collect();
+        let indicators: Vec<Indicator> = self.enemies
+            .iter()
+            .filter_map(|e| Indicator::for_chef(e, &self.camera))
+            .chain(self.butters.iter().filter_map(|b| Indicator::for_butter(b, &self.camera)))
+            .collect();
+
+        let (background_pos, background_size) = self.camera.rect(Vec2{x: 0.0, y: 0.0}, self.arena.size);
+        self.background.add_rectangle(background_pos, background_size, self.arena.tint, render_handle);
+        self.background.draw(render_handle);
         // self.enemies.iter().for_each(|b: &'o Enemy| b.draw(render_handle, &mut self.enemy_animations));
-        self.enemies.iter().for_each(|e| e.draw(render_handle, &mut self.enemy_animations));
-        self.butters.iter().for_each(|b| b.draw(render_handle, &mut self.butter_texture));
+        self.arena.draw(render_handle, &mut self.obstacle_material, &self.camera);
+        self.obstacle_material.draw(render_handle);
+        self.enemies.iter().for_each(|e| e.draw(render_handle, &mut self.enemy_animations, &self.camera));
+        self.butters.iter().for_each(|b| b.draw(render_handle, &mut self.butter_texture, &self.camera));
         for s in self.enemy_animations.iter_mut() {
             s.draw(render_handle);
         }
         self.butter_texture.draw(render_handle);
-        self.player.draw(render_handle);
-        self.text.iter_mut().for_each(|t| t.draw(render_handle));
+        self.particles.draw(render_handle, &mut self.particle_material, &self.camera);
+        self.particle_material.draw(render_handle);
+        self.players.iter().for_each(|p| p.draw(render_handle, &mut self.player_sprites, &self.camera));
+        self.player_sprites.draw(render_handle);
+
+        // a material can only be drawn once a frame so pickups and the shield go together
+        self.pickups.iter().for_each(|p| p.draw(render_handle, &mut self.pickup_materials, &self.camera));
+
+        for ((player_pos, player_size), player_hud) in bodies.iter().zip(player_huds.iter()) {
+            if player_hud.effects.iter().any(|(kind, _)| *kind == PickupKind::Shield) {
+                let (pos, size) = self.camera.rect(
+                    *player_pos - Vec2{x: 15.0, y: 15.0},
+                    Vec2{x: player_size.x + 30.0, y: player_size.y + 30.0},
+                );
+                let bubble = Colour::from_rgba(255.0, 255.0, 255.0, 140.0);
+                self.pickup_materials[PickupKind::Shield.index()].add_rectangle(pos, size, bubble, render_handle);
+            }
+        }
+
+        // a bar over anyone being picked back up
+        for (pos, progress) in revives {
+            let (back_pos, back_size) = self.camera.rect(Vec2{x: pos.x, y: pos.y - 14.0}, Vec2{x: 50.0, y: 8.0});
+            let (_, fill_size) = self.camera.rect(pos, Vec2{x: 50.0 * progress, y: 8.0});
+            self.hud_material.add_rectangle(back_pos, back_size, Colour::BLACK, render_handle);
+            self.hud_material.add_rectangle(back_pos, fill_size, Colour::GREEN, render_handle);
+        }
+
+        // chefs only get a health bar once they've been hit
+        for (pos, width, health) in health_bars {
+            let (back_pos, back_size) = self.camera.rect(Vec2{x: pos.x, y: pos.y - 10.0}, Vec2{x: width, y: 6.0});
+            let (_, fill_size) = self.camera.rect(pos, Vec2{x: width * health, y: 6.0});
+            self.hud_material.add_rectangle(back_pos, back_size, Colour::BLACK, render_handle);
+            self.hud_material.add_rectangle(back_pos, fill_size, Colour::RED, render_handle);
+        }
+
+        // chefs and butter the camera can't see yet get an arrow on the edge of the screen
+        let hud = self.camera.get_viewport();
+        for indicator in indicators.iter() {
+            indicator.draw(render_handle, &mut self.hud_material, &mut self.arrow_material, &hud);
+        }
+
+        self.pickup_materials.iter_mut().for_each(|m| m.draw(render_handle));
+        self.hud_material.draw(render_handle);
+        self.arrow_material.draw(render_handle);
+        self.damage_numbers.draw(render_handle, &self.camera);
+
+        // big arenas scroll, cover whatever of the world spilled out past the screen
+        for (pos, size) in hud.letterbox() {
+            self.letterbox_material.add_rectangle(pos, size, Colour::BLACK, render_handle);
+        }
+        self.letterbox_material.draw(render_handle);
+        self.hud.draw(render_handle, &hud, &player_huds);
     }
 
     pub fn restart(&mut self, engine_handle: &mut Engine) {
+        self.random = match self.seed {
+            Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+            None => ChaCha8Rng::from_entropy(),
+        };
+        self.next_enemy_id = 0;
+        self.arena.hazards.reset();
         self.butters = Vec::new();
         self.enemies = Vec::new();
+        self.pickups = Vec::new();
+        self.events.clear();
+        self.particles.clear();
+        self.damage_numbers.clear();
+        self.camera.reset();
         self.set_wave(1, engine_handle);
         self.total_kills = 0;
-        self.player.restart();
+        self.score = Score::new();
+        self.update_hud(engine_handle);
+        self.coins = 0;
+        self.add_coins(0, engine_handle);
+        self.time_left = self.mode.time_limit().unwrap_or(0.0);
+        let hearts = self.starting_hearts();
+        let spawns = Self::player_spawns(&self.arena, self.players.len());
+        for (player, spawn) in self.players.iter_mut().zip(spawns) {
+            player.set_spawn(spawn);
+            player.restart(self.difficulty, hearts);
+        }
+        self.camera.snap_to(self.follow_target(), self.arena.size);
+    }
+
+    /// one player per entry in `controls`, takes effect on the next restart
+    pub fn set_players(&mut self, controls: &[Controls]) {
+        let current: Vec<Controls> = self.players.iter().map(|p| p.get_controls()).collect();
+        if current == controls {
+            return;
+        }
+
+        let spawns = Self::player_spawns(&self.arena, controls.len());
+        self.players = controls
+            .iter()
+            .zip(spawns)
+            .enumerate()
+            .map(|(slot, (c, spawn))| Player::new(spawn, slot, *c))
+            .collect();
+    }
+
+    /// spread out along the middle of the arena
+    fn player_spawns(arena: &Arena, count: usize) -> Vec<Vec2<f32>> {
+        let spacing = 120.0;
+        let first_x = arena.size.x / 2.0 - spacing * (count.max(1) - 1) as f32 / 2.0;
+        (0..count)
+            .map(|slot| Vec2{x: first_x + spacing * slot as f32, y: arena.size.y / 2.0})
+            .collect()
     }
 
-    pub fn player_dead(&self) -> bool {
-        self.player.is_dead()
+    pub fn set_arena(&mut self, arena: Arena, engine_handle: &mut Engine) {
+        self.background = Self::create_background(&arena, engine_handle);
+        self.arena = arena;
+    }
+
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+    }
+
+    /// call whenever the window might have changed size
+    pub fn set_viewport(&mut self, viewport: Viewport, engine_handle: &mut Engine) {
+        self.camera.set_viewport(viewport);
+        self.hud.fit(&viewport, engine_handle);
+        self.damage_numbers.set_viewport(viewport, engine_handle);
+    }
+
+    pub fn get_viewport(&self) -> Viewport {
+        self.camera.get_viewport()
+    }
+
+    pub fn set_screen_shake(&mut self, enabled: bool) {
+        self.camera.set_shake_enabled(enabled);
+    }
+
+    pub fn get_difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    pub fn set_mode(&mut self, mode: GameMode) {
+        self.mode = mode;
+    }
+
+    pub fn get_mode(&self) -> GameMode {
+        self.mode
+    }
+
+    /// what the player in `slot` is pressing on this machine
+    pub fn read_input(&self, slot: usize, engine_handle: &Engine) -> PlayerInput {
+        self.aim_in_world(self.players[slot].read_input(engine_handle))
+    }
+
+    /// the mouse comes in as window pixels, the players aim in the world
+    fn aim_in_world(&self, mut input: PlayerInput) -> PlayerInput {
+        input.aim = input.aim.map(|aim| self.camera.to_world(aim));
+        input
+    }
+
+    /// someone is playing from another machine
+    pub fn is_online(&self) -> bool {
+        self.players.iter().any(|p| p.get_controls() == Controls::Remote)
+    }
+
+    /// a daily run replays the same chefs and drops every time
+    pub fn set_daily(&mut self, daily: Option<DailyChallenge>) {
+        self.seed = daily.as_ref().map(|d| d.seed);
+        self.daily = daily;
+    }
+
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+
+    pub fn get_daily(&self) -> Option<&DailyChallenge> {
+        self.daily.as_ref()
+    }
+
+    fn has_modifier(&self, modifier: DailyModifier) -> bool {
+        self.daily.as_ref().is_some_and(|d| d.has(modifier))
+    }
+
+    fn starting_hearts(&self) -> f32 {
+        if self.has_modifier(DailyModifier::OneHeart) {
+            return 1.0;
+        }
+        self.mode.hearts().unwrap_or(self.difficulty.player_hearts())
+    }
+
+    fn enemy_tuning(&self) -> EnemyTuning {
+        let mut tuning = EnemyTuning::for_difficulty(self.difficulty);
+        if self.has_modifier(DailyModifier::FastButter) {
+            tuning.butter_speed *= 2.0;
+        }
+        if self.has_modifier(DailyModifier::TankyChefs) {
+            tuning.hp *= 1.5;
+        }
+        tuning
+    }
+
+    pub fn get_arena_name(&self) -> &str {
+        &self.arena.name
+    }
+
+    fn create_background(arena: &Arena, engine_handle: &mut Engine) -> Material {
+        let texture = Texture::new(engine_handle, &arena.background);
+        MaterialBuilder::new().add_texture(texture).build(engine_handle)
+    }
+
+    /// everyone is down, in co-op there's nobody left to pick the others up
+    pub fn players_down(&self) -> bool {
+        self.players.iter().all(|p| p.is_dead())
+    }
+
+    fn update_revives(&mut self, dt: f32) {
+        let standing: Vec<Vec2<f32>> = self.players
+            .iter()
+            .filter(|p| !p.is_dead())
+            .map(|p| p.get_center())
+            .collect();
+
+        for player in self.players.iter_mut() {
+            let center = player.get_center();
+            let partner_near = standing
+                .iter()
+                .any(|c| (c.x - center.x).powi(2) + (c.y - center.y).powi(2) < REVIVE_RADIUS.powi(2));
+            player.update_revive(dt, partner_near);
+        }
+    }
+
+    pub fn is_time_up(&self) -> bool {
+        self.mode.time_limit().is_some() && self.time_left <= 0.0
+    }
+
+    /// the players went down or the clock ran out
+    pub fn is_run_over(&self) -> bool {
+        self.players_down() || self.is_time_up()
+    }
+
+    /// how long the run lasted in modes with a time limit
+    pub fn get_time_played(&self) -> f32 {
+        self.mode.time_limit().unwrap_or(0.0) - self.time_left.max(0.0)
+    }
+
+    fn update_clock(&mut self, dt: f32) {
+        if self.mode.time_limit().is_some() {
+            self.time_left -= dt;
+        }
+    }
+
+    fn update_hazards(&mut self, dt: f32) {
+        let hazards = &mut self.arena.hazards;
+        hazards.update(dt);
+        hazards.burn_players(&mut self.players, &mut self.events);
+        hazards.burn_enemies(&mut self.enemies, dt, &mut self.events);
+    }
+
+    fn update_hud(&mut self, engine_handle: &mut Engine) {
+        let wave_size = self.wave.size();
+        let stats = HudStats {
+            score: self.score.points(),
+            combo: (self.score.combo() * 10.0).round() as u32,
+            wave: self.wave.number,
+            wave_size,
+            chefs_left: wave_size - self.wave.spawned + self.enemies_alive() as u32,
+            kills: self.total_kills,
+            coins: self.coins,
+            time_left: self.mode.time_limit().map(|_| self.time_left.max(0.0).ceil() as u32),
+        };
+        self.hud.update(stats, engine_handle);
+    }
+
+    pub fn get_score(&self) -> u32 {
+        self.score.points()
+    }
+
+    fn drop_pickup(&mut self, pos: Vec2<f32>) {
+        if !self.random.gen_bool(PICKUP_DROP_CHANCE) {
+            return;
+        }
+
+        let kinds: Vec<PickupKind> = PickupKind::ALL
+            .into_iter()
+            .filter(|k| *k != PickupKind::Heart || self.can_heal())
+            .collect();
+        let kind = kinds[self.random.gen_range(0..kinds.len())];
+        self.pickups.push(Pickup::new(kind, pos));
+    }
+
+    fn update_pickups(&mut self, dt: f32) {
+        for pickup in self.pickups.iter_mut() {
+            pickup.update(dt);
+            let collector = self.players.iter_mut().filter(|p| !p.is_dead()).find(|p| {
+                let (hit_box_pos, hit_box_size) = p.get_hit_box();
+                collision::rect_rect(hit_box_size, hit_box_pos, pickup.size, pickup.pos)
+            });
+            if let Some(player) = collector {
+                player.apply_pickup(pickup.kind);
+                self.events.push(GameEvent::PickupCollected{pos: player.get_center(), kind: pickup.kind});
+                pickup.collect();
+            }
+        }
+
+        self.pickups.retain(|p| !p.is_expired());
     }
 
     fn spawn_enemy(&mut self, dt: f32) {
         self.spawn_timer -= dt;
 
-        if self.enemies_spawned < Self::get_enemies_to_spawn(self.wave_number) &&
+        if self.wave.spawned < self.wave.size() &&
         self.spawn_timer < 0.0 &&
-        self.enemies.len() < self.wave_number as usize + 2
+        self.enemies_alive() < self.wave.number as usize + 2
         {
-            let side: u8 = self.random.gen_range(0..4);
-            let pos: f32 = self.random.gen_range(0.0..800.0);
-            if side == 0 {
-                self.enemies.push(Enemy::new(Vec2{x: -50.0, y: pos}));
-            } else if side == 1 {
-                self.enemies.push(Enemy::new(Vec2{x: 850.0, y: pos}));
-            } else if side == 2 {
-                self.enemies.push(Enemy::new(Vec2{x: pos, y: -50.0}));
-            } else if side == 3 {
-                self.enemies.push(Enemy::new(Vec2{x: pos, y: 850.0}))
+            let pos = self.arena.spawn_position(&mut self.random);
+            self.enemies.push(Enemy::new(pos, self.next_enemy_id, self.enemy_tuning()));
+            self.next_enemy_id += 1;
+            self.spawn_timer = self.wave.spawn_interval() * self.difficulty.spawn_timer();
+            if self.has_modifier(DailyModifier::Swarm) {
+                self.spawn_timer *= 0.5;
             }
-            self.spawn_timer = Self::get_spawn_timer(self.wave_number);
-            self.enemies_spawned += 1;
+            self.wave.spawned += 1;
         }
     }
 
-    fn is_wave_over(&self) -> bool {
-        self.enemies_spawned == Self::get_enemies_to_spawn(self.wave_number) &&
-            self.enemies.len() == 0
+    /// waits for the last chef to finish falling over too
+    pub fn is_wave_over(&self) -> bool {
+        self.wave.is_over(self.enemies.len())
     }
 
-    fn set_wave(&mut self, wave: u32, engine_handle: &mut Engine) {
+    fn enemies_alive(&self) -> usize {
+        self.enemies.iter().filter(|e| e.is_alive()).count()
+    }
 
-        if wave > self.wave_number {
-            self.player.end_wave();
-        }
+    pub fn next_wave(&mut self, engine_handle: &mut Engine) {
+        self.set_wave(self.wave.number + 1, engine_handle);
+    }
 
-        self.wave_number = wave;
-        self.enemies_spawned = 0;
+    fn set_wave(&mut self, wave: u32, engine_handle: &mut Engine) {
+        self.wave = Wave::new(wave);
+        self.score.start_wave();
         self.spawn_timer = -1.0;
-        self.text[0].change_text(&format!("Wave: {}", self.wave_number), engine_handle);
-        self.text[0].pos.x = 800.0 - self.text[0].size.x as f32;
+        self.events.push(GameEvent::WaveStarted);
         self.spawn_enemy(0.0);
+        self.update_hud(engine_handle);
+    }
+
+    fn add_coins(&mut self, coins: u32, engine_handle: &mut Engine) {
+        self.coins += coins;
+        self.update_hud(engine_handle);
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            players: self.players.clone(),
+            enemies: self.enemies.clone(),
+            butters: self.butters.clone(),
+            pickups: self.pickups.clone(),
+            hazards: self.arena.hazards.clone(),
+            wave: self.wave,
+            spawn_timer: self.spawn_timer,
+            random: self.random.clone(),
+            total_kills: self.total_kills,
+            score: self.score.clone(),
+            coins: self.coins,
+            next_enemy_id: self.next_enemy_id,
+            time_left: self.time_left,
+        }
+    }
+
+    /// puts the game back how it was when `snapshot` was taken, the camera and effects carry on
+    pub fn rewind(&mut self, snapshot: &Snapshot) {
+        let snapshot = snapshot.clone();
+        self.players = snapshot.players;
+        self.enemies = snapshot.enemies;
+        self.butters = snapshot.butters;
+        self.pickups = snapshot.pickups;
+        self.arena.hazards = snapshot.hazards;
+        self.wave = snapshot.wave;
+        self.spawn_timer = snapshot.spawn_timer;
+        self.random = snapshot.random;
+        self.total_kills = snapshot.total_kills;
+        self.score = snapshot.score;
+        self.coins = snapshot.coins;
+        self.next_enemy_id = snapshot.next_enemy_id;
+        self.time_left = snapshot.time_left;
+    }
+
+    /// hands over everything that happened since the last call
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn get_coins(&self) -> u32 {
+        self.coins
+    }
+
+    /// picks `count` different upgrades for the shop to offer
+    pub fn roll_upgrades(&mut self, count: usize) -> Vec<Upgrade> {
+        let mut pool: Vec<Upgrade> = Upgrade::ALL
+            .into_iter()
+            .filter(|u| *u != Upgrade::ExtraHeart || self.can_heal())
+            .collect();
+        let mut offers = Vec::new();
+        while offers.len() < count && !pool.is_empty() {
+            let i = self.random.gen_range(0..pool.len());
+            offers.push(pool.swap_remove(i));
+        }
+        offers
+    }
+
+    pub fn roll_perks(&mut self, count: usize) -> Vec<Perk> {
+        let mut pool = Perk::ALL.to_vec();
+        let mut offers = Vec::new();
+        while offers.len() < count && !pool.is_empty() {
+            let i = self.random.gen_range(0..pool.len());
+            offers.push(pool.swap_remove(i));
+        }
+        offers
+    }
+
+    /// perks and upgrades are bought for the whole team
+    pub fn add_perk(&mut self, perk: Perk) {
+        self.players.iter_mut().for_each(|p| p.add_perk(perk));
+    }
+
+    pub fn upgrade_cost(&self, upgrade: Upgrade) -> u32 {
+        upgrade.cost(self.players[0].upgrades_owned(upgrade))
+    }
+
+    pub fn buy_upgrade(&mut self, upgrade: Upgrade, engine_handle: &mut Engine) -> bool {
+        let cost = self.upgrade_cost(upgrade);
+        if cost > self.coins {
+            return false;
+        }
+
+        self.coins -= cost;
+        self.add_coins(0, engine_handle);
+        self.players.iter_mut().for_each(|p| p.apply_upgrade(upgrade));
+        true
+    }
+
+    pub fn heal_cost(&self) -> u32 {
+        HEAL_COST
+    }
+
+    pub fn can_heal(&self) -> bool {
+        self.mode.can_heal() && !self.has_modifier(DailyModifier::NoHearts)
+    }
+
+    /// patches up whoever is worst off, that gets a downed partner back on their feet too
+    pub fn buy_heal(&mut self, engine_handle: &mut Engine) -> bool {
+        if HEAL_COST > self.coins || !self.can_heal() {
+            return false;
+        }
+        let Some(player) = self.players.iter_mut().filter(|p| p.is_hurt()).min_by_key(|p| p.get_hp()) else {
+            return false;
+        };
+
+        player.heal();
+        self.coins -= HEAL_COST;
+        self.add_coins(0, engine_handle);
+        true
     }
 
     pub fn get_wave(&self) -> u32 {
-        self.wave_number
+        self.wave.number
     }
 
     pub fn get_kills(&self) -> u32 {
         self.total_kills
     }
 
-    fn get_enemies_to_spawn(level: u32) -> u32 {
-        let level = (level - 1) as f32;
+}
+
+/// Everything a step can change that matters to how the game plays out, online games keep one
+/// from before each tick they might have to play again
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    players: Vec<Player>,
+    enemies: Vec<Enemy>,
+    butters: Vec<Butter>,
+    pickups: Vec<Pickup>,
+    hazards: Hazards,
+    wave: Wave,
+    spawn_timer: f32,
+    random: ChaCha8Rng,
+    total_kills: u32,
+    score: Score,
+    coins: u32,
+    next_enemy_id: u32,
+    time_left: f32,
+}
+
+/// How far through a wave we are, the level decides when chefs actually come in
+#[derive(Clone, Copy, Debug)]
+struct Wave {
+    number: u32,
+    spawned: u32,
+    /// so `WaveCleared` only goes out once however long the wave sits finished
+    cleared: bool,
+}
+
+impl Wave {
+    fn new(number: u32) -> Self {
+        Self {
+            number,
+            spawned: 0,
+            cleared: false,
+        }
+    }
+
+    /// how many chefs come in over the whole wave
+    fn size(&self) -> u32 {
+        let level = (self.number - 1) as f32;
         if level <= 10.0 {
             (f32::powf(level, 1.1).round() as u32 * 5) + 4
         } else {
@@ -175,9 +822,54 @@ impl Level {
         }
     }
 
-    fn get_spawn_timer(wave: u32) -> f32 {
-        let wave = (wave-1) as f32;
+    fn spawn_interval(&self) -> f32 {
+        let wave = (self.number - 1) as f32;
 
         f32::max(10.0 - 1.66 * wave, 0.3)
     }
-}
\ No newline at end of file
+
+    /// `enemies_left` counts the ones still falling over too
+    fn is_over(&self, enemies_left: usize) -> bool {
+        self.spawned == self.size() && enemies_left == 0
+    }
+
+    /// `WaveCleared` the first time it's asked once the wave is over
+    fn clear(&mut self, enemies_left: usize) -> Option<GameEvent> {
+        if self.cleared || !self.is_over(enemies_left) {
+            return None;
+        }
+
+        self.cleared = true;
+        Some(GameEvent::WaveCleared{wave: self.number})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wave_clears_once() {
+        let mut wave = Wave::new(2);
+        wave.spawned = wave.size() - 1;
+        assert_eq!(wave.clear(0), None);
+
+        // the last chef is in and then lying on the floor for a bit
+        wave.spawned += 1;
+        assert_eq!(wave.clear(1), None);
+
+        let cleared: Vec<GameEvent> = (0..5).filter_map(|_| wave.clear(0)).collect();
+        assert_eq!(cleared, vec![GameEvent::WaveCleared{wave: 2}]);
+        assert!(wave.is_over(0));
+    }
+
+    #[test]
+    fn waves_get_bigger_and_faster() {
+        for number in 1..20 {
+            let (wave, next) = (Wave::new(number), Wave::new(number + 1));
+            assert!(next.size() >= wave.size());
+            assert!(next.spawn_interval() <= wave.spawn_interval());
+        }
+        assert_eq!(Wave::new(1).size(), 4);
+    }
+}
@@ -1,12 +1,39 @@
 mod animation;
+mod arena;
+mod audio;
+mod camera;
 mod collision;
+mod daily;
+mod damage_number;
+mod difficulty;
 mod enemy;
+mod event;
+mod hazard;
+mod highscore;
+mod hud;
+mod indicator;
 mod level;
+mod mode;
+mod net;
+mod particle;
+mod perk;
+mod pickup;
 mod text;
 mod player;
+mod score;
+mod shop;
+mod steering;
+mod upgrade;
+mod versus;
+mod viewport;
 
 
-use bottomless_pit::input::MouseKey;
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use bottomless_pit::input::{Key, MouseKey};
 use bottomless_pit::engine_handle::Engine;
 use bottomless_pit::colour::Colour;
 use bottomless_pit::engine_handle::EngineBuilder;
@@ -14,15 +41,26 @@ use bottomless_pit::material::{Material, MaterialBuilder};
 use bottomless_pit::render::RenderInformation;
 use bottomless_pit::texture::Texture;
 use bottomless_pit::vectors::Vec2;
-use level::Level;
+use arena::Arena;
+use audio::Audio;
+use daily::DailyChallenge;
+use difficulty::Difficulty;
+use highscore::{DailyRecord, DailyScores, HighScores, Record};
+use level::{Level, Snapshot};
+use player::Controls;
+use mode::GameMode;
+use net::{MatchSettings, NetSession};
+use shop::Shop;
 use text::Text;
+use versus::Versus;
+use viewport::{Viewport, LOGICAL_SIZE};
 
 fn main() {
     let mut engine = EngineBuilder::new()
-        .set_clear_colour(Colour::BLUE)
+        .set_clear_colour(Colour::BLACK)
         .set_window_title("Butter My Biscuit!")
-        .unresizable()
         .with_resolution((800, 800))
+        .set_window_min_size((400, 400))
         .build()
         .unwrap();
 
@@ -37,54 +75,167 @@ struct Biscut {
     logo: Material,
     plain_mat: Material,
     level: Level,
+    arenas: Vec<Arena>,
+    high_scores: HighScores,
+    daily_scores: DailyScores,
+    /// the challenge shown on the daily screen, rolled when it's opened
+    daily: Option<DailyChallenge>,
+    shop: Option<Shop>,
+    difficulty: Difficulty,
+    mode: GameMode,
+    /// a second player on the other side of the keyboard
+    coop: bool,
+    /// some people need the screen to hold still, hit-stop stays either way
+    screen_shake: bool,
+    /// the online game being set up or played
+    net: Option<NetSession>,
+    /// the level from before every online tick that might still have to be played again
+    snapshots: BTreeMap<u32, Snapshot>,
+    /// what's been typed into the lobby so far
+    address: String,
+    versus: Option<Versus>,
+    audio: Audio,
+    /// where the 800 by 800 game sits in the window, refit every frame
+    viewport: Viewport,
+    fullscreen: bool,
     state: MainState,
 }
 
+/// the lobby's on screen keys, there's no text input so addresses get clicked in
+const KEYPAD: [&str; 13] = ["1", "2", "3", "4", "5", "6", "7", "8", "9", ".", "0", ":", "Del"];
+
 impl bottomless_pit::Game for Biscut {
     fn render<'p, 'o>(&'o mut self, mut render_handle: RenderInformation<'p, 'o>) where 'o: 'p {
-        self.bg_texture.add_rectangle(Vec2{x: 0.0, y: 0.0}, Vec2{x: 800.0, y: 800.0}, Colour::WHITE, &render_handle);
-        self.bg_texture.draw(&mut render_handle);
-
+        let viewport = self.viewport;
         match self.state {
             MainState::InGame => {
                 self.level.draw(&mut render_handle);
             },
+            MainState::Shop => {
+                self.level.draw(&mut render_handle);
+                if let Some(shop) = self.shop.as_mut() {
+                    shop.draw(&mut render_handle);
+                }
+            },
+            MainState::Versus => {
+                if let Some(versus) = self.versus.as_mut() {
+                    versus.draw(&mut render_handle);
+                }
+            },
+            MainState::VersusEnd => {
+                if let Some(versus) = self.versus.as_mut() {
+                    versus.draw(&mut render_handle);
+                }
+                for button in self.text[3..5].iter() {
+                    let (pos, size) = button.button_rect();
+                    let (pos, size) = viewport.rect(pos, size);
+                    self.plain_mat.add_rectangle(pos, size, Colour::WHITE, &render_handle);
+                }
+                self.plain_mat.draw(&mut render_handle);
+            },
             MainState::EndMenu => {
                 self.level.draw(&mut render_handle);
-                let r1_pos = self.text[3].pos - Vec2{x: 10.0, y: 10.0};
-                let r1_size = Vec2{x: self.text[3].size.x as f32 + 20.0, y: self.text[3].size.y as f32 + 20.0};
-                let r2_pos = self.text[4].pos - Vec2{x: 10.0, y: 10.0};
-                let r2_size = Vec2{x: self.text[4].size.x as f32 + 20.0, y: self.text[4].size.y as f32 + 20.0};
-
-                self.plain_mat.add_rectangle(r1_pos, r1_size, Colour::WHITE, &render_handle);
-                self.plain_mat.add_rectangle(r2_pos, r2_size, Colour::WHITE, &render_handle);
+                for button in self.text[3..5].iter() {
+                    let (pos, size) = button.button_rect();
+                    let (pos, size) = viewport.rect(pos, size);
+                    self.plain_mat.add_rectangle(pos, size, Colour::WHITE, &render_handle);
+                }
                 self.plain_mat.draw(&mut render_handle);
             },
             MainState::MainMenu => {
-                let r1_pos = self.text[0].pos - Vec2{x: 10.0, y: 10.0};
-                let r1_size = Vec2{x: self.text[0].size.x as f32 + 20.0, y: self.text[0].size.y as f32 + 20.0};
-                let r2_pos = self.text[1].pos - Vec2{x: 10.0, y: 10.0};
-                let r2_size = Vec2{x: self.text[1].size.x as f32 + 20.0, y: self.text[1].size.y as f32 + 20.0};
-
-                self.logo.add_rectangle(Vec2{x: 250.0, y: 250.0}, Vec2{x: 300.0, y: 300.0}, Colour::WHITE, &render_handle);
-
-                self.plain_mat.add_rectangle(r1_pos, r1_size, Colour::WHITE, &render_handle);
-                self.plain_mat.add_rectangle(r2_pos, r2_size, Colour::WHITE, &render_handle);
+                let (pos, size) = viewport.rect(Vec2{x: 0.0, y: 0.0}, LOGICAL_SIZE);
+                self.bg_texture.add_rectangle(pos, size, Colour::WHITE, &render_handle);
+                self.bg_texture.draw(&mut render_handle);
+
+                let (pos, size) = viewport.rect(Vec2{x: 250.0, y: 250.0}, Vec2{x: 300.0, y: 300.0});
+                self.logo.add_rectangle(pos, size, Colour::WHITE, &render_handle);
+                for button in [&self.text[0], &self.text[1], &self.text[6], &self.text[7], &self.text[8], &self.text[9], &self.text[11], &self.text[12], &self.text[13]] {
+                    let (pos, size) = button.button_rect();
+                    let (pos, size) = viewport.rect(pos, size);
+                    self.plain_mat.add_rectangle(pos, size, Colour::WHITE, &render_handle);
+                }
 
                 self.logo.draw(&mut render_handle);
                 self.plain_mat.draw(&mut render_handle);
             },
+            MainState::ArenaSelect => {
+                let (pos, size) = viewport.rect(Vec2{x: 0.0, y: 0.0}, LOGICAL_SIZE);
+                self.bg_texture.add_rectangle(pos, size, Colour::WHITE, &render_handle);
+                self.bg_texture.draw(&mut render_handle);
+
+                // back button and one button per arena, the best score labels come after them
+                for button in self.text[1..self.arenas.len() + 2].iter() {
+                    let (pos, size) = button.button_rect();
+                    let (pos, size) = viewport.rect(pos, size);
+                    self.plain_mat.add_rectangle(pos, size, Colour::WHITE, &render_handle);
+                }
+                self.plain_mat.draw(&mut render_handle);
+            },
+            MainState::Daily => {
+                let (pos, size) = viewport.rect(Vec2{x: 0.0, y: 0.0}, LOGICAL_SIZE);
+                self.bg_texture.add_rectangle(pos, size, Colour::WHITE, &render_handle);
+                self.bg_texture.draw(&mut render_handle);
+
+                // back and start
+                for button in self.text[1..3].iter() {
+                    let (pos, size) = button.button_rect();
+                    let (pos, size) = viewport.rect(pos, size);
+                    self.plain_mat.add_rectangle(pos, size, Colour::WHITE, &render_handle);
+                }
+                self.plain_mat.draw(&mut render_handle);
+            },
+            MainState::Lobby => {
+                let (pos, size) = viewport.rect(Vec2{x: 0.0, y: 0.0}, LOGICAL_SIZE);
+                self.bg_texture.add_rectangle(pos, size, Colour::WHITE, &render_handle);
+                self.bg_texture.draw(&mut render_handle);
+
+                // back, host, join and the keypad, the address and status labels sit between them
+                let buttons = self.text[1..4].iter().chain(self.text[6..].iter());
+                for button in buttons {
+                    let (pos, size) = button.button_rect();
+                    let (pos, size) = viewport.rect(pos, size);
+                    self.plain_mat.add_rectangle(pos, size, Colour::WHITE, &render_handle);
+                }
+                self.plain_mat.draw(&mut render_handle);
+            },
         }
-        self.text.iter_mut().for_each(|t| t.draw(&mut render_handle));
+        self.text.iter_mut().for_each(|t| t.draw(&mut render_handle, &viewport));
     }
 
     fn update(&mut self, engine_handle: &mut Engine) {
         let dt = engine_handle.get_frame_delta_time();
         match self.state {
+            MainState::InGame if self.net.is_some() => self.online_update(engine_handle, dt),
             MainState::InGame => self.in_game_update(engine_handle, dt),
+            MainState::Lobby => self.lobby_update(engine_handle, dt),
             MainState::MainMenu => self.main_menu_update(engine_handle),
+            MainState::ArenaSelect => self.arena_select_update(engine_handle),
+            MainState::Daily => self.daily_update(engine_handle),
+            MainState::Shop => self.shop_update(engine_handle),
             MainState::EndMenu => self.end_menu_update(engine_handle, dt),
+            MainState::Versus => self.versus_update(engine_handle, dt),
+            MainState::VersusEnd => self.versus_end_update(engine_handle),
+        }
+
+        let mut events = self.level.take_events();
+        if let Some(versus) = self.versus.as_mut() {
+            events.extend(versus.take_events());
+        }
+        self.audio.play_events(&events);
+
+        // the music builds up the longer a run goes and calms down in the menus
+        let wave = match self.state {
+            MainState::InGame | MainState::Shop => Some(self.level.get_wave()),
+            MainState::Versus => self.versus.as_ref().map(|v| v.get_round()),
+            _ => None,
+        };
+        self.audio.set_wave(wave);
+
+        if engine_handle.is_key_released(Key::F11) {
+            self.fullscreen = !self.fullscreen;
+            engine_handle.set_window_fullscreen(self.fullscreen);
         }
+        self.fit_viewport(engine_handle);
     }
 }
 
@@ -96,66 +247,399 @@ impl Biscut {
         let logo_tex = Texture::new(engine_handle, "assets/logo.png");
         let logo = MaterialBuilder::new().add_texture(logo_tex).build(engine_handle);
 
-        let text = vec![
+        let arenas = Arena::load_all();
+
+        Self {
+            level: Level::new(arenas[0].clone(), engine_handle),
+            logo,
+            text: Self::main_menu_text(Difficulty::Normal, GameMode::Endless, false, true, engine_handle),
+            bg_texture,
+            plain_mat: MaterialBuilder::new().build(engine_handle),
+            arenas,
+            high_scores: HighScores::load(),
+            daily_scores: DailyScores::load(),
+            daily: None,
+            shop: None,
+            difficulty: Difficulty::Normal,
+            mode: GameMode::Endless,
+            coop: false,
+            screen_shake: true,
+            net: None,
+            snapshots: BTreeMap::new(),
+            versus: None,
+            address: format!("127.0.0.1:{}", net::DEFAULT_PORT),
+            audio: Audio::new(),
+            viewport: Viewport::new(),
+            fullscreen: false,
+            state: MainState::MainMenu,
+        }
+    }
+
+    /// letterboxes the game into however big the window is right now, the text gets rebuilt
+    /// at the new size so it doesn't go blurry
+    fn fit_viewport(&mut self, engine_handle: &mut Engine) {
+        let viewport = Viewport::fit(engine_handle.get_window_size());
+        self.viewport = viewport;
+        self.text.iter_mut().for_each(|t| t.fit(&viewport, engine_handle));
+        self.level.set_viewport(viewport, engine_handle);
+        if let Some(shop) = self.shop.as_mut() {
+            shop.set_viewport(viewport, engine_handle);
+        }
+        if let Some(versus) = self.versus.as_mut() {
+            versus.set_viewport(viewport, engine_handle);
+        }
+    }
+
+    fn main_menu_text(difficulty: Difficulty, mode: GameMode, coop: bool, screen_shake: bool, engine_handle: &mut Engine) -> Vec<Text> {
+        let mut difficulty = Text::new(&format!("Difficulty: {}", difficulty.name()), 35.0, Vec2{x: 0.0, y: 690.0}, Colour::BLACK, engine_handle);
+        difficulty.pos.x = 780.0 - difficulty.size.x as f32;
+        let mut mode = Text::new(&format!("Mode: {}", mode.name()), 35.0, Vec2{x: 0.0, y: 630.0}, Colour::BLACK, engine_handle);
+        mode.pos.x = 780.0 - mode.size.x as f32;
+        let mut players = Text::new(Self::players_label(coop), 35.0, Vec2{x: 0.0, y: 570.0}, Colour::BLACK, engine_handle);
+        players.pos.x = 780.0 - players.size.x as f32;
+        let mut shake = Text::new(Self::screen_shake_label(screen_shake), 35.0, Vec2{x: 0.0, y: 510.0}, Colour::BLACK, engine_handle);
+        shake.pos.x = 780.0 - shake.size.x as f32;
+
+        vec![
             Text::new("Start Game", 50.0, Vec2{x: 20.0, y: 600.0}, Colour::BLACK, engine_handle),
             Text::new("Quit", 50.0, Vec2{x: 20.0, y: 680.0}, Colour::BLACK, engine_handle),
             Text::new("How to play:", 35.0, Vec2{x: 20.0, y: 20.0}, Colour::BLACK, engine_handle),
             Text::new("W A S D to move", 25.0, Vec2{x: 40.0, y: 60.0}, Colour::BLACK, engine_handle),
             Text::new("Hold left click to charge", 25.0, Vec2{x: 40.0, y: 90.0}, Colour::BLACK, engine_handle),
             Text::new("Release left click to parry incoming butter", 25.0, Vec2{x: 40.0, y: 120.0}, Colour::BLACK, engine_handle),
-        ];
+            difficulty,
+            mode,
+            Text::new("Daily Challenge", 40.0, Vec2{x: 20.0, y: 530.0}, Colour::BLACK, engine_handle),
+            players,
+            Text::new("Player 2: I J K L to move, hold U to charge", 25.0, Vec2{x: 40.0, y: 150.0}, Colour::BLACK, engine_handle),
+            Text::new("Play Online", 40.0, Vec2{x: 20.0, y: 460.0}, Colour::BLACK, engine_handle),
+            Text::new("Versus", 40.0, Vec2{x: 20.0, y: 390.0}, Colour::BLACK, engine_handle),
+            shake,
+            Text::new("F11 for fullscreen", 25.0, Vec2{x: 40.0, y: 180.0}, Colour::BLACK, engine_handle),
+        ]
+    }
 
-        Self {
-            level: Level::new(engine_handle),
-            logo,
-            text,
-            bg_texture,
-            plain_mat: MaterialBuilder::new().build(engine_handle),
-            state: MainState::MainMenu,
+    fn local_controls(&self) -> &'static [Controls] {
+        if self.coop {
+            &[Controls::Mouse, Controls::Keyboard]
+        } else {
+            &[Controls::Mouse]
+        }
+    }
+
+    fn screen_shake_label(screen_shake: bool) -> &'static str {
+        if screen_shake {
+            "Screen shake: On"
+        } else {
+            "Screen shake: Off"
+        }
+    }
+
+    fn players_label(coop: bool) -> &'static str {
+        if coop {
+            "Players: 2"
+        } else {
+            "Players: 1"
         }
     }
 
     fn in_game_update(&mut self, engine_handle: &mut Engine, dt: f32) {
         self.level.update(engine_handle, dt);
-        if self.level.player_dead() {
+        if self.level.is_run_over() {
+            self.to_end(engine_handle);
+        } else if self.level.is_wave_over() && self.level.get_mode().has_shop() {
+            self.shop = Some(Shop::new(&mut self.level, engine_handle));
+            self.state = MainState::Shop;
+        } else if self.level.is_wave_over() {
+            self.level.next_wave(engine_handle);
+        }
+    }
+
+    /// online games step in fixed ticks, guessing the other player's input when it's late and
+    /// rewinding when the guess was wrong. The shop is skipped since there's no way to agree on
+    /// what to buy yet
+    fn online_update(&mut self, engine_handle: &mut Engine, dt: f32) {
+        let Some(net) = self.net.as_mut() else {
+            return;
+        };
+
+        let local = self.level.read_input(net.local_slot(), engine_handle);
+        net.update(dt, local);
+        self.level.update_camera(dt);
+
+        if net.is_disconnected() {
+            self.net = None;
+            self.to_end(engine_handle);
+            self.text[0].change_text("Lost the other player", engine_handle);
+            self.text[0].pos.x = 400.0 - self.text[0].size.x as f32 / 2.0;
+            return;
+        }
+
+        // go back to the first tick we guessed wrong and play up to now again with what really happened
+        let replay = net.take_rollback();
+        if let Some(snapshot) = replay.first().and_then(|(tick, _)| self.snapshots.get(tick)) {
+            self.level.rewind(snapshot);
+            for (tick, inputs) in replay {
+                self.snapshots.insert(tick, self.level.snapshot());
+                self.level.resimulate(&inputs, net::TICK);
+                if self.level.is_wave_over() && !self.level.is_run_over() {
+                    self.level.next_wave(engine_handle);
+                }
+            }
+        }
+
+        // however many ticks this frame's time covers, as long as we're not too far ahead
+        while let Some((tick, inputs)) = net.next_inputs() {
+            self.snapshots.insert(tick, self.level.snapshot());
+            self.level.step(&inputs, engine_handle, net::TICK);
+            if self.level.is_wave_over() && !self.level.is_run_over() {
+                self.level.next_wave(engine_handle);
+            }
+        }
+
+        let confirmed = net.confirmed_tick();
+        self.snapshots.retain(|t, _| *t >= confirmed);
+
+        // a guess could have lost the run, only call it once both sides agree it's over
+        if self.level.is_run_over() && net.is_confirmed() {
+            self.net = None;
             self.to_end(engine_handle);
         }
     }
 
+    fn lobby_update(&mut self, engine_handle: &mut Engine, dt: f32) {
+        if let Some(settings) = self.net.as_mut().and_then(|n| n.poll_lobby(dt)) {
+            self.start_online(settings, engine_handle);
+            return;
+        }
+
+        let mouse_pos = self.viewport.to_logical(engine_handle.get_mouse_position());
+        if !engine_handle.is_mouse_key_pressed(MouseKey::Left) {
+            return;
+        }
+
+        if self.text[1].is_clicked(mouse_pos) {
+            self.net = None;
+            self.text = Self::main_menu_text(self.difficulty, self.mode, self.coop, self.screen_shake, engine_handle);
+            self.state = MainState::MainMenu;
+            return;
+        }
+
+        let status = if self.text[2].is_clicked(mouse_pos) {
+            let settings = MatchSettings {
+                seed: rand::random(),
+                arena: rand::thread_rng().gen_range(0..self.arenas.len()),
+                difficulty: self.difficulty,
+                mode: self.mode,
+            };
+            match NetSession::host(net::DEFAULT_PORT, settings) {
+                Ok(session) => {
+                    self.net = Some(session);
+                    format!("Waiting for someone on port {}...", net::DEFAULT_PORT)
+                },
+                Err(e) => format!("Couldn't host: {}", e),
+            }
+        } else if self.text[3].is_clicked(mouse_pos) {
+            match self.address.parse::<SocketAddr>().map(NetSession::join) {
+                Ok(Ok(session)) => {
+                    self.net = Some(session);
+                    format!("Knocking on {}...", self.address)
+                },
+                Ok(Err(e)) => format!("Couldn't join: {}", e),
+                Err(_) => "That doesn't look like ip:port".to_string(),
+            }
+        } else {
+            let Some(key) = (0..KEYPAD.len()).find(|i| self.text[i + 6].is_clicked(mouse_pos)) else {
+                return;
+            };
+            match KEYPAD[key] {
+                "Del" => {
+                    self.address.pop();
+                },
+                c => self.address.push_str(c),
+            }
+            self.text[4].change_text(&format!("Address: {}", self.address), engine_handle);
+            return;
+        };
+
+        self.text[5].change_text(&status, engine_handle);
+    }
+
+    fn start_online(&mut self, settings: MatchSettings, engine_handle: &mut Engine) {
+        let Some(net) = self.net.as_ref() else {
+            return;
+        };
+        let controls = if net.local_slot() == 0 {
+            [Controls::Mouse, Controls::Remote]
+        } else {
+            [Controls::Remote, Controls::Mouse]
+        };
+
+        let arena = settings.arena.min(self.arenas.len() - 1);
+        self.level.set_arena(self.arenas[arena].clone(), engine_handle);
+        self.level.set_difficulty(settings.difficulty);
+        self.level.set_mode(settings.mode);
+        self.level.set_daily(None);
+        self.level.set_seed(Some(settings.seed));
+        self.level.set_players(&controls);
+        self.snapshots.clear();
+        self.to_game();
+        self.level.restart(engine_handle);
+    }
+
+    fn versus_update(&mut self, engine_handle: &mut Engine, dt: f32) {
+        let Some(versus) = self.versus.as_mut() else {
+            return;
+        };
+
+        if versus.update(engine_handle, dt) {
+            self.to_versus_end(engine_handle);
+        }
+    }
+
+    fn versus_end_update(&mut self, engine_handle: &mut Engine) {
+        let mouse_pos = self.viewport.to_logical(engine_handle.get_mouse_position());
+        if !engine_handle.is_mouse_key_pressed(MouseKey::Left) {
+            return;
+        }
+
+        if self.text[3].is_clicked(mouse_pos) {
+            if let Some(versus) = self.versus.as_mut() {
+                versus.restart(engine_handle);
+            }
+            self.text = Vec::new();
+            self.state = MainState::Versus;
+        } else if self.text[4].is_clicked(mouse_pos) {
+            self.versus = None;
+            self.text = Self::main_menu_text(self.difficulty, self.mode, self.coop, self.screen_shake, engine_handle);
+            self.state = MainState::MainMenu;
+        }
+    }
+
+    fn shop_update(&mut self, engine_handle: &mut Engine) {
+        let Some(shop) = self.shop.as_mut() else {
+            return;
+        };
+
+        if shop.update(&mut self.level, engine_handle) {
+            self.shop = None;
+            self.level.next_wave(engine_handle);
+            self.state = MainState::InGame;
+        }
+    }
+
     fn main_menu_update(&mut self, engine_handle: &mut Engine) {
-        let mouse_pos = engine_handle.get_mouse_position();
+        let mouse_pos = self.viewport.to_logical(engine_handle.get_mouse_position());
         let mouse_down = engine_handle.is_mouse_key_pressed(MouseKey::Left);
 
-        let r1_pos = self.text[0].pos - Vec2{x: 10.0, y: 10.0};
-        let r1_size = Vec2{x: self.text[0].size.x as f32 + 20.0, y: self.text[0].size.y as f32 + 20.0};
-        let r2_pos = self.text[1].pos - Vec2{x: 10.0, y: 10.0};
-        let r2_size = Vec2{x: self.text[1].size.x as f32 + 20.0, y: self.text[1].size.y as f32 + 20.0};
-
-        if mouse_down && collision::point_in_rect(r2_size, r2_pos, mouse_pos) {
+        if mouse_down && self.text[1].is_clicked(mouse_pos) {
             engine_handle.close();
         }
 
-        if mouse_down && collision::point_in_rect(r1_size, r1_pos, mouse_pos) {
+        if mouse_down && self.text[0].is_clicked(mouse_pos) {
+            self.to_arena_select(engine_handle);
+        } else if mouse_down && self.text[8].is_clicked(mouse_pos) {
+            self.to_daily(engine_handle);
+        } else if mouse_down && self.text[11].is_clicked(mouse_pos) {
+            self.to_lobby(engine_handle);
+        } else if mouse_down && self.text[12].is_clicked(mouse_pos) {
+            // both players need to be on screen at once so versus sticks to the small arenas,
+            // the button does nothing if none of them are small enough
+            let arenas: Vec<&Arena> = self.arenas.iter().filter(|a| a.fits_on_screen()).collect();
+            let Some(arena) = arenas.choose(&mut rand::thread_rng()).map(|a| (*a).clone()) else {
+                return;
+            };
+            let mut versus = Versus::new(arena, engine_handle);
+            versus.set_screen_shake(self.screen_shake);
+            self.versus = Some(versus);
+            self.text = Vec::new();
+            self.state = MainState::Versus;
+        } else if mouse_down && self.text[6].is_clicked(mouse_pos) {
+            self.difficulty = self.difficulty.next();
+            self.text[6].change_text(&format!("Difficulty: {}", self.difficulty.name()), engine_handle);
+            self.text[6].pos.x = 780.0 - self.text[6].size.x as f32;
+        } else if mouse_down && self.text[7].is_clicked(mouse_pos) {
+            self.mode = self.mode.next();
+            self.text[7].change_text(&format!("Mode: {}", self.mode.name()), engine_handle);
+            self.text[7].pos.x = 780.0 - self.text[7].size.x as f32;
+        } else if mouse_down && self.text[9].is_clicked(mouse_pos) {
+            self.coop = !self.coop;
+            self.text[9].change_text(Self::players_label(self.coop), engine_handle);
+            self.text[9].pos.x = 780.0 - self.text[9].size.x as f32;
+        } else if mouse_down && self.text[13].is_clicked(mouse_pos) {
+            self.screen_shake = !self.screen_shake;
+            self.level.set_screen_shake(self.screen_shake);
+            self.text[13].change_text(Self::screen_shake_label(self.screen_shake), engine_handle);
+            self.text[13].pos.x = 780.0 - self.text[13].size.x as f32;
+        }
+    }
+
+    fn arena_select_update(&mut self, engine_handle: &mut Engine) {
+        let mouse_pos = self.viewport.to_logical(engine_handle.get_mouse_position());
+        let mouse_down = engine_handle.is_mouse_key_pressed(MouseKey::Left);
+        if !mouse_down {
+            return;
+        }
+
+        if self.text[1].is_clicked(mouse_pos) {
+            self.text = Self::main_menu_text(self.difficulty, self.mode, self.coop, self.screen_shake, engine_handle);
+            self.state = MainState::MainMenu;
+            return;
+        }
+
+        let picked = (0..self.arenas.len()).find(|i| self.text[i + 2].is_clicked(mouse_pos));
+        if let Some(i) = picked {
+            self.level.set_arena(self.arenas[i].clone(), engine_handle);
+            self.level.set_difficulty(self.difficulty);
+            self.level.set_mode(self.mode);
+            self.level.set_daily(None);
+            self.level.set_players(self.local_controls());
             self.to_game();
+            self.level.restart(engine_handle);
+        }
+    }
+
+    fn daily_update(&mut self, engine_handle: &mut Engine) {
+        let mouse_pos = self.viewport.to_logical(engine_handle.get_mouse_position());
+        if !engine_handle.is_mouse_key_pressed(MouseKey::Left) {
+            return;
+        }
+
+        if self.text[1].is_clicked(mouse_pos) {
+            self.text = Self::main_menu_text(self.difficulty, self.mode, self.coop, self.screen_shake, engine_handle);
+            self.state = MainState::MainMenu;
+            return;
+        }
+
+        // dailies are always endless on normal so everyone plays the same run
+        if !self.text[2].is_clicked(mouse_pos) {
+            return;
+        }
+        if let Some(daily) = self.daily.take() {
+            self.level.set_arena(self.arenas[daily.arena].clone(), engine_handle);
+            self.level.set_difficulty(Difficulty::Normal);
+            self.level.set_mode(GameMode::Endless);
+            self.level.set_daily(Some(daily));
+            self.level.set_players(self.local_controls());
+            self.to_game();
+            self.level.restart(engine_handle);
         }
     }
 
     fn end_menu_update(&mut self, engine_handle: &mut Engine, dt: f32) {
         self.level.dead_update(engine_handle, dt);
 
-        let mouse_pos = engine_handle.get_mouse_position();
+        let mouse_pos = self.viewport.to_logical(engine_handle.get_mouse_position());
         let mouse_down = engine_handle.is_mouse_key_pressed(MouseKey::Left);
 
-        let r1_pos = self.text[3].pos - Vec2{x: 10.0, y: 10.0};
-        let r1_size = Vec2{x: self.text[3].size.x as f32 + 20.0, y: self.text[3].size.y as f32 + 20.0};
-        let r2_pos = self.text[4].pos - Vec2{x: 10.0, y: 10.0};
-        let r2_size = Vec2{x: self.text[4].size.x as f32 + 20.0, y: self.text[4].size.y as f32 + 20.0};
-
-        if mouse_down && collision::point_in_rect(r1_size, r1_pos, mouse_pos) {
+        if mouse_down && self.text[3].is_clicked(mouse_pos) {
             engine_handle.close();
         }
 
-        if mouse_down && collision::point_in_rect(r2_size, r2_pos, mouse_pos) {
+        // an online run can't be restarted on our own, go find the other player again
+        if mouse_down && self.text[4].is_clicked(mouse_pos) && self.level.is_online() {
+            self.to_lobby(engine_handle);
+        } else if mouse_down && self.text[4].is_clicked(mouse_pos) {
             self.to_game();
             self.level.restart(engine_handle);
         }
@@ -166,21 +650,167 @@ impl Biscut {
         self.state = MainState::InGame;
     }
 
+    fn to_daily(&mut self, engine_handle: &mut Engine) {
+        self.state = MainState::Daily;
+        let daily = DailyChallenge::today(self.arenas.len());
+        let date = daily.date.to_string();
+
+        let mut title = Text::new(&format!("Daily challenge {}", date), 45.0, Vec2{x: 0.0, y: 60.0}, Colour::BLACK, engine_handle);
+        title.pos.x = 400.0 - title.size.x as f32 / 2.0;
+        let back = Text::new("Back", 40.0, Vec2{x: 20.0, y: 720.0}, Colour::BLACK, engine_handle);
+        let mut start = Text::new("Start", 40.0, Vec2{x: 0.0, y: 720.0}, Colour::BLACK, engine_handle);
+        start.pos.x = 780.0 - start.size.x as f32;
+        let arena = Text::new(&format!("Kitchen: {}", self.arenas[daily.arena].name), 35.0, Vec2{x: 60.0, y: 150.0}, Colour::BLACK, engine_handle);
+
+        self.text = vec![title, back, start, arena];
+        for (i, modifier) in daily.modifiers.iter().enumerate() {
+            let y = 210.0 + i as f32 * 50.0;
+            let label = format!("{} - {}", modifier.name(), modifier.description());
+            self.text.push(Text::new(&label, 28.0, Vec2{x: 60.0, y}, Colour::BLACK, engine_handle));
+        }
+
+        let today = match self.daily_scores.best(&date) {
+            Some(record) => format!("Today's best: {} (wave {})", record.score, record.wave),
+            None => "Today's best: -".to_string(),
+        };
+        self.text.push(Text::new(&today, 35.0, Vec2{x: 60.0, y: 340.0}, Colour::BLACK, engine_handle));
+        self.text.push(Text::new("Recent days:", 30.0, Vec2{x: 60.0, y: 410.0}, Colour::BLACK, engine_handle));
+        for (i, record) in self.daily_scores.recent(5).iter().enumerate() {
+            let y = 450.0 + i as f32 * 45.0;
+            let label = format!("{}   {} (wave {})", record.date, record.score, record.wave);
+            self.text.push(Text::new(&label, 28.0, Vec2{x: 80.0, y}, Colour::BLACK, engine_handle));
+        }
+
+        self.daily = Some(daily);
+    }
+
+    fn to_versus_end(&mut self, engine_handle: &mut Engine) {
+        let Some(versus) = self.versus.as_ref() else {
+            return;
+        };
+        self.state = MainState::VersusEnd;
+
+        let wins = versus.get_wins();
+        let winner = versus.winner().unwrap_or(0);
+        let mut title = Text::new(&format!("Player {} wins!", winner + 1), 50.0, Vec2{x: 0.0, y: 220.0}, Colour::BLACK, engine_handle);
+        let mut score = Text::new(&format!("{} - {}", wins[0], wins[1]), 40.0, Vec2{x: 0.0, y: 290.0}, Colour::BLACK, engine_handle);
+        let mut format = Text::new(&format!("First to {} rounds", versus::ROUNDS_TO_WIN), 30.0, Vec2{x: 0.0, y: 340.0}, Colour::BLACK, engine_handle);
+        let mut rematch = Text::new("Rematch", 40.0, Vec2{x: 0.0, y: 430.0}, Colour::BLACK, engine_handle);
+        let mut menu = Text::new("Main menu", 40.0, Vec2{x: 0.0, y: 510.0}, Colour::BLACK, engine_handle);
+        for text in [&mut title, &mut score, &mut format, &mut rematch, &mut menu] {
+            text.pos.x = 400.0 - text.size.x as f32 / 2.0;
+        }
+
+        // the buttons sit at 3 and 4 like on the normal end screen
+        self.text = vec![title, score, format, rematch, menu];
+    }
+
+    fn to_lobby(&mut self, engine_handle: &mut Engine) {
+        self.state = MainState::Lobby;
+
+        let mut title = Text::new("Online co-op", 50.0, Vec2{x: 0.0, y: 60.0}, Colour::BLACK, engine_handle);
+        title.pos.x = 400.0 - title.size.x as f32 / 2.0;
+        let back = Text::new("Back", 40.0, Vec2{x: 20.0, y: 720.0}, Colour::BLACK, engine_handle);
+        let host = Text::new(&format!("Host on port {}", net::DEFAULT_PORT), 40.0, Vec2{x: 60.0, y: 200.0}, Colour::BLACK, engine_handle);
+        let join = Text::new("Join address", 40.0, Vec2{x: 60.0, y: 290.0}, Colour::BLACK, engine_handle);
+        let address = Text::new(&format!("Address: {}", self.address), 30.0, Vec2{x: 60.0, y: 580.0}, Colour::BLACK, engine_handle);
+        let status = Text::new("The host plays on their difficulty and mode", 25.0, Vec2{x: 60.0, y: 640.0}, Colour::BLACK, engine_handle);
+
+        self.text = vec![title, back, host, join, address, status];
+        for (i, key) in KEYPAD.iter().enumerate() {
+            let pos = Vec2{x: 480.0 + (i % 3) as f32 * 90.0, y: 180.0 + (i / 3) as f32 * 75.0};
+            self.text.push(Text::new(key, 40.0, pos, Colour::BLACK, engine_handle));
+        }
+    }
+
+    fn to_arena_select(&mut self, engine_handle: &mut Engine) {
+        self.state = MainState::ArenaSelect;
+
+        let mut title = Text::new("Pick a kitchen", 50.0, Vec2{x: 0.0, y: 60.0}, Colour::BLACK, engine_handle);
+        title.pos.x = 400.0 - title.size.x as f32 / 2.0;
+        let mut subtitle = Text::new(&format!("{} - {}", self.mode.name(), self.difficulty.name()), 30.0, Vec2{x: 0.0, y: 120.0}, Colour::BLACK, engine_handle);
+        subtitle.pos.x = 400.0 - subtitle.size.x as f32 / 2.0;
+        let back = Text::new("Back", 40.0, Vec2{x: 20.0, y: 720.0}, Colour::BLACK, engine_handle);
+
+        let mut buttons = Vec::new();
+        let mut bests = Vec::new();
+        for (i, arena) in self.arenas.iter().enumerate() {
+            let y = 200.0 + i as f32 * 110.0;
+            buttons.push(Text::new(&arena.name, 40.0, Vec2{x: 60.0, y}, Colour::BLACK, engine_handle));
+
+            let best = match self.high_scores.best(&arena.name, self.difficulty, self.mode) {
+                Some(record) if self.mode == GameMode::TimeAttack => format!("Best: {} chefs ({})", record.kills, record.score),
+                Some(record) => format!("Best: {} (wave {})", record.score, record.wave),
+                None => "Best: -".to_string(),
+            };
+            bests.push(Text::new(&best, 30.0, Vec2{x: 360.0, y: y + 5.0}, Colour::BLACK, engine_handle));
+        }
+
+        self.text = vec![title, back];
+        self.text.append(&mut buttons);
+        self.text.append(&mut bests);
+        self.text.push(subtitle);
+    }
+
     fn to_end(&mut self, engine_handle: &mut Engine) {
         self.state = MainState::EndMenu;
-        let mut text_1 = Text::new("Congrats!", 40.0, Vec2{x: 400.0, y: 230.0}, Colour::BLACK, engine_handle);
-        let mut text_2 = Text::new(&format!("You made it to wave: {}", self.level.get_wave()), 40.0, Vec2{x: 0.0, y: 270.0}, Colour::BLACK, engine_handle);
-        let mut text_3 = Text::new(&format!("Succesfully fought {} chefs", self.level.get_kills()), 40.0, Vec2{x: 0.0, y: 310.0}, Colour::BLACK, engine_handle);
+
+        let new_best = match self.level.get_daily() {
+            Some(daily) => self.daily_scores.submit(DailyRecord {
+                date: daily.date.to_string(),
+                wave: self.level.get_wave(),
+                kills: self.level.get_kills(),
+                score: self.level.get_score(),
+            }),
+            None => self.high_scores.submit(Record {
+                arena: self.level.get_arena_name().to_string(),
+                difficulty: self.level.get_difficulty(),
+                mode: self.level.get_mode(),
+                wave: self.level.get_wave(),
+                kills: self.level.get_kills(),
+                score: self.level.get_score(),
+            }),
+        };
+
+        let title = if new_best {
+            "New best!"
+        } else if self.level.is_time_up() {
+            "Time's up!"
+        } else {
+            "Congrats!"
+        };
+
+        let (summary_1, summary_2) = match self.level.get_mode() {
+            GameMode::TimeAttack => (
+                format!("Fought {} chefs in {}", self.level.get_kills(), mode::format_time(self.level.get_time_played())),
+                format!("You made it to wave: {}", self.level.get_wave()),
+            ),
+            GameMode::OneHit => (
+                format!("One heart lasted to wave: {}", self.level.get_wave()),
+                format!("Succesfully fought {} chefs", self.level.get_kills()),
+            ),
+            GameMode::Endless | GameMode::Survival => (
+                format!("You made it to wave: {}", self.level.get_wave()),
+                format!("Succesfully fought {} chefs", self.level.get_kills()),
+            ),
+        };
+
+        let mut text_1 = Text::new(title, 40.0, Vec2{x: 400.0, y: 230.0}, Colour::BLACK, engine_handle);
+        let mut text_2 = Text::new(&summary_1, 40.0, Vec2{x: 0.0, y: 270.0}, Colour::BLACK, engine_handle);
+        let mut text_3 = Text::new(&summary_2, 40.0, Vec2{x: 0.0, y: 310.0}, Colour::BLACK, engine_handle);
         text_1.pos.x = 400.0 - text_1.size.x as f32 / 2.0;
         text_2.pos.x = 400.0 - text_2.size.x as f32 / 2.0;
         text_3.pos.x = 400.0 - text_3.size.x as f32 / 2.0;
+        let mut score = Text::new(&format!("Final score: {}", self.level.get_score()), 40.0, Vec2{x: 0.0, y: 350.0}, Colour::BLACK, engine_handle);
+        score.pos.x = 400.0 - score.size.x as f32 / 2.0;
 
-        let mut restart = Text::new("Restart", 40.0, Vec2{x: 0.0, y: 390.0}, Colour::BLACK, engine_handle);
+        let mut restart = Text::new("Restart", 40.0, Vec2{x: 0.0, y: 430.0}, Colour::BLACK, engine_handle);
         restart.pos.x = 400.0 - restart.size.x as f32 / 2.0;
-        let mut quit = Text::new("Quit", 40.0, Vec2{x: 0.0, y: 470.0}, Colour::BLACK, engine_handle);
+        let mut quit = Text::new("Quit", 40.0, Vec2{x: 0.0, y: 510.0}, Colour::BLACK, engine_handle);
         quit.pos.x = 400.0 - quit.size.x as f32 / 2.0;
 
-        self.text = vec![text_1, text_2, text_3, restart, quit];
+        // the buttons stay at 3 and 4, end_menu_update looks for them there
+        self.text = vec![text_1, text_2, text_3, restart, quit, score];
     }
 }
 
@@ -188,8 +818,14 @@ impl Biscut {
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum MainState {
     MainMenu,
+    ArenaSelect,
     InGame,
+    Shop,
     EndMenu,
+    Daily,
+    Lobby,
+    Versus,
+    VersusEnd,
 }
 
 fn move_towards(current: Vec2<f32>, target: Vec2<f32>, max_distance: f32) -> Vec2<f32> {
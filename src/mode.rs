@@ -0,0 +1,80 @@
+/// What a run is played for, picked on the main menu next to the difficulty
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameMode {
+    /// waves until the biscuit crumbles
+    Endless,
+    /// as many chefs as possible before the clock runs out
+    TimeAttack,
+    /// waves until death but hearts never come back
+    Survival,
+    /// a single heart for the whole run
+    OneHit,
+}
+
+impl GameMode {
+    pub const ALL: [Self; 4] = [Self::Endless, Self::TimeAttack, Self::Survival, Self::OneHit];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Endless => "Endless",
+            Self::TimeAttack => "Time Attack",
+            Self::Survival => "Survival",
+            Self::OneHit => "One Hit",
+        }
+    }
+
+    /// what gets written to the save file
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::Endless => "endless",
+            Self::TimeAttack => "time_attack",
+            Self::Survival => "survival",
+            Self::OneHit => "one_hit",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|m| m.key() == key)
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Endless => Self::TimeAttack,
+            Self::TimeAttack => Self::Survival,
+            Self::Survival => Self::OneHit,
+            Self::OneHit => Self::Endless,
+        }
+    }
+
+    /// seconds until the run ends on its own
+    pub fn time_limit(&self) -> Option<f32> {
+        match self {
+            Self::TimeAttack => Some(180.0),
+            _ => None,
+        }
+    }
+
+    /// time attack runs the waves back to back so the clock is all that matters
+    pub fn has_shop(&self) -> bool {
+        *self != Self::TimeAttack
+    }
+
+    /// whether lost hearts can come back from the shop, heart pickups or extra heart upgrades
+    pub fn can_heal(&self) -> bool {
+        matches!(self, Self::Endless | Self::TimeAttack)
+    }
+
+    /// overrides the hearts the difficulty would give
+    pub fn hearts(&self) -> Option<f32> {
+        match self {
+            Self::OneHit => Some(1.0),
+            _ => None,
+        }
+    }
+}
+
+/// minutes and seconds, rounded up so the clock never shows 0:00 while there is still time
+pub fn format_time(seconds: f32) -> String {
+    let seconds = seconds.max(0.0).ceil() as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
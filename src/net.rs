@@ -0,0 +1,575 @@
+use std::collections::BTreeMap;
+use std::io::{self, ErrorKind};
+use std::net::{SocketAddr, UdpSocket};
+
+use bottomless_pit::vectors::Vec2;
+
+use crate::difficulty::Difficulty;
+use crate::mode::GameMode;
+use crate::player::PlayerInput;
+
+pub const DEFAULT_PORT: u16 = 7777;
+/// online games always step by the same amount so both sides play the same ticks
+pub const TICK: f32 = 1.0 / 60.0;
+/// ticks between reading an input and playing it, most of the time it reaches the other side first
+const INPUT_DELAY: u32 = 2;
+/// how many ticks we'll guess the other side's input for before waiting on them instead
+const MAX_ROLLBACK: u32 = 8;
+/// most ticks we'll play in one frame when catching up, past that the game just runs slow
+const MAX_TICKS_PER_FRAME: f32 = 5.0;
+/// every packet carries this many of our latest inputs so a lost one doesn't stall the game
+const INPUT_REDUNDANCY: u32 = 8;
+const HELLO_INTERVAL: f32 = 0.5;
+/// seconds without hearing from the other side before we give up on them
+const TIMEOUT: f32 = 5.0;
+const PROTOCOL_VERSION: u8 = 1;
+
+const HELLO: u8 = 0;
+const WELCOME: u8 = 1;
+const INPUTS: u8 = 2;
+
+/// Everything both sides need to start the same run, the host picks it and sends it over
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MatchSettings {
+    pub seed: u64,
+    /// index into the arenas the game loaded
+    pub arena: usize,
+    pub difficulty: Difficulty,
+    pub mode: GameMode,
+}
+
+impl MatchSettings {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.push(self.arena as u8);
+        bytes.push(Difficulty::ALL.iter().position(|d| *d == self.difficulty).unwrap_or(0) as u8);
+        bytes.push(GameMode::ALL.iter().position(|m| *m == self.mode).unwrap_or(0) as u8);
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let seed = u64::from_le_bytes(bytes.get(0..8)?.try_into().ok()?);
+        Some(Self {
+            seed,
+            arena: *bytes.get(8)? as usize,
+            difficulty: *Difficulty::ALL.get(*bytes.get(9)? as usize)?,
+            mode: *GameMode::ALL.get(*bytes.get(10)? as usize)?,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Role {
+    Host,
+    Guest,
+}
+
+/// Two player co-op over UDP with rollback. Each side sends the inputs it reads a couple of ticks
+/// ahead of when they get played. When the other side's input for a tick hasn't turned up yet we
+/// guess they're still doing what they last did and carry on, and if the real one turns out
+/// different the game rewinds to that tick and plays it again. With the same seed both machines
+/// end up simulating exactly the same thing.
+pub struct NetSession {
+    socket: UdpSocket,
+    role: Role,
+    peer: Option<SocketAddr>,
+    settings: Option<MatchSettings>,
+    /// the next tick to be played
+    tick: u32,
+    /// every tick before this has the other side's real input in
+    confirmed: u32,
+    local_inputs: BTreeMap<u32, PlayerInput>,
+    remote_inputs: BTreeMap<u32, PlayerInput>,
+    /// the other side's latest real input, the guess for every tick they haven't sent yet
+    last_remote: PlayerInput,
+    /// both players' inputs in slot order for every tick that got played on a guess
+    played: BTreeMap<u32, [PlayerInput; 2]>,
+    /// the first tick whose guess turned out wrong
+    rollback: Option<u32>,
+    /// the last input we read, it carries on into every tick played before the next frame
+    last_local: PlayerInput,
+    /// frame time that hasn't been played out in ticks yet
+    accumulator: f32,
+    hello_timer: f32,
+    since_heard: f32,
+}
+
+impl NetSession {
+    /// waits on `port` for someone to join, they get `settings`
+    pub fn host(port: u16, settings: MatchSettings) -> io::Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", port))?;
+        Self::new(socket, Role::Host, None, Some(settings))
+    }
+
+    pub fn join(address: SocketAddr) -> io::Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+        Self::new(socket, Role::Guest, Some(address), None)
+    }
+
+    fn new(socket: UdpSocket, role: Role, peer: Option<SocketAddr>, settings: Option<MatchSettings>) -> io::Result<Self> {
+        socket.set_nonblocking(true)?;
+
+        // nobody has inputs for the first few ticks, both sides stand still for them
+        let idle: BTreeMap<u32, PlayerInput> = (0..INPUT_DELAY).map(|t| (t, PlayerInput::default())).collect();
+
+        Ok(Self {
+            socket,
+            role,
+            peer,
+            settings,
+            tick: 0,
+            confirmed: INPUT_DELAY,
+            local_inputs: idle.clone(),
+            remote_inputs: idle,
+            last_remote: PlayerInput::default(),
+            played: BTreeMap::new(),
+            rollback: None,
+            last_local: PlayerInput::default(),
+            accumulator: 0.0,
+            hello_timer: 0.0,
+            since_heard: 0.0,
+        })
+    }
+
+    /// the host is player one and whoever joins is player two
+    pub fn local_slot(&self) -> usize {
+        match self.role {
+            Role::Host => 0,
+            Role::Guest => 1,
+        }
+    }
+
+    /// call every frame from the lobby, returns the settings once both sides know about each other
+    pub fn poll_lobby(&mut self, dt: f32) -> Option<MatchSettings> {
+        self.receive(dt);
+
+        if self.role == Role::Guest && self.settings.is_none() {
+            self.hello_timer -= dt;
+            if self.hello_timer <= 0.0 {
+                self.hello_timer = HELLO_INTERVAL;
+                self.send(&[HELLO, PROTOCOL_VERSION]);
+            }
+        }
+
+        self.peer?;
+        self.settings
+    }
+
+    /// call every frame during the game, picks up the other side's inputs and resends ours in case
+    /// they went missing. `local` gets queued if the game has moved on since the last one
+    pub fn update(&mut self, dt: f32, local: PlayerInput) {
+        self.receive(dt);
+
+        self.accumulator = (self.accumulator + dt).min(TICK * MAX_TICKS_PER_FRAME);
+        self.last_local = local;
+        self.queue_local();
+        self.send_inputs();
+    }
+
+    /// the next tick and both players' inputs for it in slot order. `None` once this frame's time
+    /// has been played out or while we're too far ahead of the other side, so call it until it runs dry
+    pub fn next_inputs(&mut self) -> Option<(u32, [PlayerInput; 2])> {
+        if self.accumulator < TICK || self.tick >= self.confirmed + MAX_ROLLBACK {
+            return None;
+        }
+
+        let tick = self.tick;
+        let inputs = self.inputs_for(tick, *self.local_inputs.get(&tick)?);
+        if tick >= self.confirmed {
+            self.played.insert(tick, inputs);
+        }
+        self.tick += 1;
+        self.accumulator -= TICK;
+
+        // old ones are only kept around to resend, the other side has moved past these
+        let oldest = self.tick.saturating_sub(INPUT_REDUNDANCY);
+        self.local_inputs.retain(|t, _| *t >= oldest);
+        // nothing before the confirmed tick can be rolled back to any more
+        if self.rollback.is_none() {
+            let confirmed = self.confirmed_tick();
+            self.played.retain(|t, _| *t >= confirmed);
+            self.remote_inputs.retain(|t, _| *t >= confirmed);
+        }
+        // a release only happens once, the extra ticks this frame just keep holding
+        self.last_local.released = false;
+        self.queue_local();
+
+        Some((tick, inputs))
+    }
+
+    /// Every tick that has to be played again because a guess turned out wrong, oldest first with
+    /// the inputs to play it with this time. Empty when the guesses have all held up
+    pub fn take_rollback(&mut self) -> Vec<(u32, [PlayerInput; 2])> {
+        let Some(from) = self.rollback.take() else {
+            return Vec::new();
+        };
+
+        (from..self.tick).map(|tick| {
+            let local = self.played.get(&tick).map_or(PlayerInput::default(), |p| p[self.local_slot()]);
+            let inputs = self.inputs_for(tick, local);
+            self.played.insert(tick, inputs);
+            (tick, inputs)
+        }).collect()
+    }
+
+    /// every tick before this has been played with both sides' real inputs, nothing older
+    /// will ever be rolled back to
+    pub fn confirmed_tick(&self) -> u32 {
+        self.confirmed.min(self.tick)
+    }
+
+    /// true when nothing played so far was a guess
+    pub fn is_confirmed(&self) -> bool {
+        self.confirmed >= self.tick && self.rollback.is_none()
+    }
+
+    /// the other side's input when it's in, otherwise a guess that they're still doing the same
+    /// thing. A release only happens once so that never gets guessed
+    fn inputs_for(&self, tick: u32, local: PlayerInput) -> [PlayerInput; 2] {
+        let remote = self.remote_inputs
+            .get(&tick)
+            .copied()
+            .unwrap_or(PlayerInput{released: false, ..self.last_remote});
+
+        match self.role {
+            Role::Host => [local, remote],
+            Role::Guest => [remote, local],
+        }
+    }
+
+    /// moves the confirmed tick up past every real input that's in, and marks where to roll
+    /// back to if one of them isn't what got played
+    fn confirm(&mut self) {
+        let remote_slot = 1 - self.local_slot();
+        while let Some(&input) = self.remote_inputs.get(&self.confirmed) {
+            let tick = self.confirmed;
+            if self.played.get(&tick).is_some_and(|p| p[remote_slot] != input) {
+                self.rollback = Some(self.rollback.map_or(tick, |r| r.min(tick)));
+            }
+            self.last_remote = input;
+            self.confirmed += 1;
+        }
+    }
+
+    fn queue_local(&mut self) {
+        let next = self.tick + INPUT_DELAY;
+        self.local_inputs.entry(next).or_insert(self.last_local);
+    }
+
+    pub fn is_disconnected(&self) -> bool {
+        self.since_heard > TIMEOUT
+    }
+
+    fn receive(&mut self, dt: f32) {
+        self.since_heard += dt;
+
+        let mut buffer = [0; 512];
+        loop {
+            let (len, from) = match self.socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                // on windows a peer closing shows up as an error on our side, just keep going
+                Err(e) if e.kind() == ErrorKind::ConnectionReset => continue,
+                // nothing left to read, or the socket is broken and the timeout will catch it
+                Err(_) => break,
+            };
+
+            if self.peer.is_some_and(|p| p != from) {
+                continue;
+            }
+            self.handle(&buffer[..len], from);
+        }
+    }
+
+    fn handle(&mut self, packet: &[u8], from: SocketAddr) {
+        let Some((&kind, body)) = packet.split_first() else {
+            return;
+        };
+
+        match (kind, self.role) {
+            (HELLO, Role::Host) if body.first() == Some(&PROTOCOL_VERSION) => {
+                self.peer = Some(from);
+                self.since_heard = 0.0;
+                // they keep saying hello until this gets through
+                if let Some(settings) = self.settings {
+                    let mut welcome = vec![WELCOME, PROTOCOL_VERSION];
+                    settings.encode(&mut welcome);
+                    self.send(&welcome);
+                }
+            },
+            (WELCOME, Role::Guest) if body.first() == Some(&PROTOCOL_VERSION) => {
+                self.settings = self.settings.or(MatchSettings::decode(&body[1..]));
+                self.since_heard = 0.0;
+            },
+            (INPUTS, _) if self.peer.is_some() => {
+                self.since_heard = 0.0;
+                self.read_inputs(body);
+            },
+            _ => {},
+        }
+    }
+
+    fn send_inputs(&mut self) {
+        let Some(first) = self.local_inputs.keys().next().copied() else {
+            return;
+        };
+
+        let mut packet = vec![INPUTS];
+        packet.extend_from_slice(&first.to_le_bytes());
+        packet.push(self.local_inputs.len() as u8);
+        for input in self.local_inputs.values() {
+            encode_input(input, &mut packet);
+        }
+        self.send(&packet);
+    }
+
+    fn read_inputs(&mut self, body: &[u8]) {
+        let Some(first) = body.get(0..4).and_then(|b| b.try_into().ok()).map(u32::from_le_bytes) else {
+            return;
+        };
+        let Some(&count) = body.get(4) else {
+            return;
+        };
+
+        for i in 0..count as usize {
+            let start = 5 + i * INPUT_SIZE;
+            let Some(input) = body.get(start..start + INPUT_SIZE).and_then(decode_input) else {
+                break;
+            };
+
+            let tick = first + i as u32;
+            if tick >= self.confirmed {
+                self.remote_inputs.entry(tick).or_insert(input);
+            }
+        }
+        self.confirm();
+    }
+
+    fn send(&self, packet: &[u8]) {
+        if let Some(peer) = self.peer {
+            // lost packets get resent anyway, nothing to do about a failed send
+            let _ = self.socket.send_to(packet, peer);
+        }
+    }
+}
+
+/// a byte of flags and the aim point
+const INPUT_SIZE: usize = 9;
+
+fn encode_input(input: &PlayerInput, bytes: &mut Vec<u8>) {
+    let flags = [input.up, input.down, input.left, input.right, input.charging, input.released, input.aim.is_some()]
+        .iter()
+        .enumerate()
+        .fold(0u8, |flags, (i, set)| flags | (u8::from(*set) << i));
+    let aim = input.aim.unwrap_or(Vec2{x: 0.0, y: 0.0});
+
+    bytes.push(flags);
+    bytes.extend_from_slice(&aim.x.to_le_bytes());
+    bytes.extend_from_slice(&aim.y.to_le_bytes());
+}
+
+fn decode_input(bytes: &[u8]) -> Option<PlayerInput> {
+    let flags = *bytes.first()?;
+    let flag = |i: u8| flags & (1 << i) != 0;
+    let x = f32::from_le_bytes(bytes.get(1..5)?.try_into().ok()?);
+    let y = f32::from_le_bytes(bytes.get(5..9)?.try_into().ok()?);
+
+    Some(PlayerInput {
+        up: flag(0),
+        down: flag(1),
+        left: flag(2),
+        right: flag(3),
+        charging: flag(4),
+        released: flag(5),
+        aim: flag(6).then_some(Vec2{x, y}),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> MatchSettings {
+        MatchSettings {
+            seed: 0x0123_4567_89ab_cdef,
+            arena: 3,
+            difficulty: Difficulty::ALL[Difficulty::ALL.len() - 1],
+            mode: GameMode::ALL[GameMode::ALL.len() - 1],
+        }
+    }
+
+    fn input(i: u32) -> PlayerInput {
+        PlayerInput {
+            up: i & 1 == 0,
+            right: true,
+            charging: i > 3,
+            aim: Some(Vec2{x: i as f32 * 10.5, y: -2.25}),
+            ..Default::default()
+        }
+    }
+
+    /// an inputs packet body starting at `first`
+    fn inputs_body(first: u32, inputs: &[PlayerInput]) -> Vec<u8> {
+        let mut body = first.to_le_bytes().to_vec();
+        body.push(inputs.len() as u8);
+        inputs.iter().for_each(|i| encode_input(i, &mut body));
+        body
+    }
+
+    #[test]
+    fn input_round_trips() {
+        let inputs = [
+            PlayerInput::default(),
+            PlayerInput{up: true, down: true, left: true, right: true, charging: true, released: true, aim: Some(Vec2{x: 123.5, y: -0.75})},
+            PlayerInput{released: true, ..Default::default()},
+            input(7),
+        ];
+
+        for input in inputs {
+            let mut bytes = Vec::new();
+            encode_input(&input, &mut bytes);
+            assert_eq!(bytes.len(), INPUT_SIZE);
+            assert_eq!(decode_input(&bytes), Some(input));
+        }
+    }
+
+    #[test]
+    fn truncated_input_is_rejected() {
+        let mut bytes = Vec::new();
+        encode_input(&input(1), &mut bytes);
+        assert_eq!(decode_input(&bytes[..INPUT_SIZE - 1]), None);
+        assert_eq!(decode_input(&[]), None);
+    }
+
+    #[test]
+    fn settings_round_trip() {
+        let mut bytes = Vec::new();
+        settings().encode(&mut bytes);
+        assert_eq!(MatchSettings::decode(&bytes), Some(settings()));
+        assert_eq!(MatchSettings::decode(&bytes[..bytes.len() - 1]), None);
+    }
+
+    #[test]
+    fn settings_with_unknown_mode_are_rejected() {
+        let mut bytes = Vec::new();
+        settings().encode(&mut bytes);
+        bytes[10] = GameMode::ALL.len() as u8;
+        assert_eq!(MatchSettings::decode(&bytes), None);
+    }
+
+    #[test]
+    fn read_inputs_keeps_whole_inputs_from_a_truncated_packet() {
+        let mut net = NetSession::host(0, settings()).unwrap();
+        let sent: Vec<PlayerInput> = (0..3).map(input).collect();
+        let body = inputs_body(4, &sent);
+
+        // the last input is cut off half way
+        net.read_inputs(&body[..body.len() - 4]);
+        assert_eq!(net.remote_inputs.get(&4), Some(&sent[0]));
+        assert_eq!(net.remote_inputs.get(&5), Some(&sent[1]));
+        assert_eq!(net.remote_inputs.get(&6), None);
+
+        // no room for even the header
+        net.read_inputs(&body[..4]);
+        net.read_inputs(&[]);
+        assert_eq!(net.remote_inputs.len(), INPUT_DELAY as usize + 2);
+    }
+
+    #[test]
+    fn steps_as_many_ticks_as_the_frame_covers() {
+        let mut net = NetSession::host(0, settings()).unwrap();
+        let remote: Vec<PlayerInput> = (INPUT_DELAY..INPUT_DELAY + 8).map(input).collect();
+        net.read_inputs(&inputs_body(INPUT_DELAY, &remote));
+
+        // not a whole tick yet
+        net.update(TICK * 0.5, PlayerInput::default());
+        assert!(net.next_inputs().is_none());
+
+        net.update(TICK * 3.0, input(1));
+        let stepped = std::iter::from_fn(|| net.next_inputs()).count();
+        assert_eq!(stepped, 3);
+        assert_eq!(net.tick, 3);
+    }
+
+    #[test]
+    fn only_guesses_so_far_ahead() {
+        let mut net = NetSession::host(0, settings()).unwrap();
+
+        let mut stepped = 0;
+        for _ in 0..10 {
+            net.update(TICK * 10.0, PlayerInput::default());
+            stepped += std::iter::from_fn(|| net.next_inputs()).count();
+        }
+        // the idle ticks at the start are known for both sides, past them it's all guesses
+        assert_eq!(stepped, (INPUT_DELAY + MAX_ROLLBACK) as usize);
+        assert!(!net.is_confirmed());
+
+        // the time waited doesn't pile up past a few ticks
+        assert!(net.accumulator <= TICK * MAX_TICKS_PER_FRAME);
+    }
+
+    #[test]
+    fn guesses_the_last_real_input_without_the_release() {
+        let mut net = NetSession::host(0, settings()).unwrap();
+        let last = PlayerInput{released: true, ..input(5)};
+        net.read_inputs(&inputs_body(INPUT_DELAY, &[input(4), last]));
+
+        net.update(TICK * 5.0, PlayerInput::default());
+        let played: Vec<(u32, [PlayerInput; 2])> = std::iter::from_fn(|| net.next_inputs()).collect();
+
+        assert_eq!(played.len(), 5);
+        assert_eq!(played[INPUT_DELAY as usize + 1].1[1], last);
+        assert_eq!(played[4].1[1], input(5));
+    }
+
+    #[test]
+    fn a_wrong_guess_rolls_back_to_where_it_started() {
+        let mut net = NetSession::host(0, settings()).unwrap();
+        net.update(TICK * 5.0, input(9));
+        let played: Vec<(u32, [PlayerInput; 2])> = std::iter::from_fn(|| net.next_inputs()).collect();
+        assert_eq!(played.len(), 5);
+
+        // the first tick they sent matches what we guessed, the next one doesn't
+        let real = [PlayerInput::default(), input(1)];
+        net.read_inputs(&inputs_body(INPUT_DELAY, &real));
+        assert_eq!(net.confirmed_tick(), INPUT_DELAY + 2);
+
+        let replay = net.take_rollback();
+        let ticks: Vec<u32> = replay.iter().map(|(tick, _)| *tick).collect();
+        assert_eq!(ticks, (INPUT_DELAY + 1..5).collect::<Vec<u32>>());
+        // the real input where it's in and a fresh guess from it after that
+        assert!(replay.iter().all(|(_, [_, remote])| *remote == input(1)));
+        // our own side plays out the same as it did the first time
+        for (tick, [local, _]) in replay.iter() {
+            assert_eq!(*local, played[*tick as usize].1[0]);
+        }
+
+        assert!(net.take_rollback().is_empty());
+    }
+
+    #[test]
+    fn a_right_guess_doesnt_roll_back() {
+        let mut net = NetSession::host(0, settings()).unwrap();
+        net.update(TICK * 4.0, PlayerInput::default());
+        std::iter::from_fn(|| net.next_inputs()).for_each(drop);
+
+        net.read_inputs(&inputs_body(INPUT_DELAY, &[PlayerInput::default(); 2]));
+        assert!(net.take_rollback().is_empty());
+        assert!(net.is_confirmed());
+    }
+
+    #[test]
+    fn a_release_is_only_played_once() {
+        let mut net = NetSession::host(0, settings()).unwrap();
+        let remote = vec![PlayerInput::default(); 8];
+        net.read_inputs(&inputs_body(INPUT_DELAY, &remote));
+
+        let release = PlayerInput{released: true, ..Default::default()};
+        net.update(TICK * 5.0, release);
+        let played: Vec<(u32, [PlayerInput; 2])> = std::iter::from_fn(|| net.next_inputs()).collect();
+
+        assert_eq!(played.len(), 5);
+        assert!(played[INPUT_DELAY as usize].1[0].released);
+        net.update(TICK * 4.0, PlayerInput::default());
+        let later: Vec<(u32, [PlayerInput; 2])> = std::iter::from_fn(|| net.next_inputs()).collect();
+        assert!(later.iter().all(|(_, [local, _])| !local.released));
+    }
+}
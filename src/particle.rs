@@ -0,0 +1,358 @@
+use std::f32::consts::PI;
+
+use bottomless_pit::colour::Colour;
+use bottomless_pit::material::Material;
+use bottomless_pit::render::RenderInformation;
+use bottomless_pit::vectors::Vec2;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::arena::parse_numbers;
+use crate::camera::Camera;
+use crate::event::GameEvent;
+
+/// past this new particles are dropped until old ones die off
+const MAX_PARTICLES: usize = 512;
+
+/// Everything needed to spit out one burst of particles
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Emitter {
+    pub count: u32,
+    /// picked at random between the two for every particle
+    pub speed: (f32, f32),
+    pub lifetime: (f32, f32),
+    pub size: (f32, f32),
+    pub colour: (f32, f32, f32, f32),
+    /// fraction of its speed a particle keeps every second
+    pub speed_kept: f32,
+    /// pulls downwards, in units per second squared
+    pub gravity: f32,
+}
+
+// the bursts are data so they can be tuned without touching the code, baked in like the arenas
+const EMITTER_FILE: &str = include_str!("../assets/particles/bursts.emitters");
+
+impl Emitter {
+    /// what an emitter starts as before its lines fill it in
+    const BLANK: Self = Self {
+        count: 0,
+        speed: (0.0, 0.0),
+        lifetime: (0.0, 0.0),
+        size: (0.0, 0.0),
+        colour: (255.0, 255.0, 255.0, 255.0),
+        speed_kept: 1.0,
+        gravity: 0.0,
+    };
+}
+
+/// Every burst the game uses, read from an emitters file
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Emitters {
+    /// bits of butter flying off whatever it hit
+    pub butter_splat: Emitter,
+    /// a chef going down in a cloud of flour
+    pub flour_puff: Emitter,
+    /// a perfect parry
+    pub sparkle: Emitter,
+    /// something got picked up, in the colour of whatever it was
+    pub pickup_glow: Emitter,
+}
+
+impl Emitters {
+    pub fn load() -> Self {
+        Self::parse(EMITTER_FILE).unwrap_or_else(|e| panic!("bursts.emitters: {}", e))
+    }
+
+    /// Parses an emitters file. `emitter <name>` starts a new one and every line after it
+    /// is a keyword and its values, anything left out stays as it is in `Emitter::BLANK`.
+    /// Blank lines and lines starting with `#` are skipped.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut emitters: Vec<(String, Emitter)> = Vec::new();
+
+        for (line_number, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+            let rest = rest.trim();
+            let error = |msg: &str| format!("line {}: {}", line_number + 1, msg);
+
+            if keyword == "emitter" {
+                if emitters.iter().any(|(name, _)| name == rest) {
+                    return Err(error(&format!("emitter `{}` given twice", rest)));
+                }
+                emitters.push((rest.to_string(), Emitter::BLANK));
+                continue;
+            }
+
+            let Some((_, emitter)) = emitters.last_mut() else {
+                return Err(error(&format!("`{}` before any emitter", keyword)));
+            };
+
+            match keyword {
+                "count" => {
+                    let [count] = parse_numbers(rest).map_err(|e| error(&e))?;
+                    emitter.count = count as u32;
+                },
+                "speed" => emitter.speed = parse_range(rest).map_err(|e| error(&e))?,
+                "lifetime" => emitter.lifetime = parse_range(rest).map_err(|e| error(&e))?,
+                "size" => emitter.size = parse_range(rest).map_err(|e| error(&e))?,
+                "colour" => {
+                    let [r, g, b, a] = parse_numbers(rest).map_err(|e| error(&e))?;
+                    emitter.colour = (r, g, b, a);
+                },
+                "speed_kept" => [emitter.speed_kept] = parse_numbers(rest).map_err(|e| error(&e))?,
+                "gravity" => [emitter.gravity] = parse_numbers(rest).map_err(|e| error(&e))?,
+                _ => return Err(error(&format!("unknown keyword `{}`", keyword))),
+            }
+        }
+
+        let mut take = |name: &str| {
+            let i = emitters.iter().position(|(n, _)| n == name).ok_or(format!("missing emitter `{}`", name))?;
+            Ok::<Emitter, String>(emitters.remove(i).1)
+        };
+        let parsed = Self {
+            butter_splat: take("butter_splat")?,
+            flour_puff: take("flour_puff")?,
+            sparkle: take("sparkle")?,
+            pickup_glow: take("pickup_glow")?,
+        };
+
+        match emitters.first() {
+            Some((name, _)) => Err(format!("unknown emitter `{}`", name)),
+            None => Ok(parsed),
+        }
+    }
+
+    /// which burst goes with `event`, and where
+    pub fn for_event(&self, event: &GameEvent) -> Option<(Emitter, Vec2<f32>)> {
+        match event {
+            GameEvent::Hit{pos, ..} => Some((self.butter_splat, *pos)),
+            GameEvent::Kill{pos, ..} => Some((self.flour_puff, *pos)),
+            GameEvent::Reflect{pos, perfect: true} => Some((self.sparkle, *pos)),
+            GameEvent::PickupCollected{pos, kind} => Some((Emitter{colour: kind.colour(), ..self.pickup_glow}, *pos)),
+            _ => None,
+        }
+    }
+}
+
+/// a low and high end that have to go the right way round for `gen_range`
+fn parse_range(s: &str) -> Result<(f32, f32), String> {
+    let [low, high] = parse_numbers(s)?;
+    if low > high {
+        return Err(format!("{} is bigger than {}", low, high));
+    }
+    Ok((low, high))
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Particle {
+    pos: Vec2<f32>,
+    velocity: Vec2<f32>,
+    size: f32,
+    colour: (f32, f32, f32, f32),
+    speed_kept: f32,
+    gravity: f32,
+    lifetime: f32,
+    age: f32,
+}
+
+impl Particle {
+    fn is_alive(&self) -> bool {
+        self.age < self.lifetime
+    }
+
+    /// from 1 when it's fresh down to 0 when it's gone
+    fn life_left(&self) -> f32 {
+        (1.0 - self.age / self.lifetime).max(0.0)
+    }
+}
+
+/// Purely cosmetic bits that get thrown out on hits and kills. Has its own random numbers so
+/// none of this can knock the level's seeded ones out of step
+pub struct Particles {
+    /// dead particles stay in here and get reused by the next burst
+    pool: Vec<Particle>,
+    emitters: Emitters,
+    random: StdRng,
+}
+
+impl Particles {
+    pub fn new() -> Self {
+        Self {
+            pool: Vec::new(),
+            emitters: Emitters::load(),
+            random: StdRng::from_entropy(),
+        }
+    }
+
+    pub fn emit(&mut self, emitter: &Emitter, pos: Vec2<f32>) {
+        for _ in 0..emitter.count {
+            let angle = self.random.gen_range(0.0..2.0 * PI);
+            let speed = self.random.gen_range(emitter.speed.0..=emitter.speed.1);
+            let particle = Particle {
+                pos,
+                velocity: Vec2{x: angle.cos() * speed, y: angle.sin() * speed},
+                size: self.random.gen_range(emitter.size.0..=emitter.size.1),
+                colour: emitter.colour,
+                speed_kept: emitter.speed_kept,
+                gravity: emitter.gravity,
+                lifetime: self.random.gen_range(emitter.lifetime.0..=emitter.lifetime.1),
+                age: 0.0,
+            };
+
+            match self.pool.iter().position(|p| !p.is_alive()) {
+                Some(slot) => self.pool[slot] = particle,
+                None if self.pool.len() < MAX_PARTICLES => self.pool.push(particle),
+                None => return,
+            }
+        }
+    }
+
+    /// a burst for everything in `events` that has one
+    pub fn handle_events(&mut self, events: &[GameEvent]) {
+        for event in events {
+            if let Some((emitter, pos)) = self.emitters.for_event(event) {
+                self.emit(&emitter, pos);
+            }
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for particle in self.pool.iter_mut().filter(|p| p.is_alive()) {
+            let keep = particle.speed_kept.powf(dt);
+            particle.velocity.x *= keep;
+            particle.velocity.y = particle.velocity.y * keep + particle.gravity * dt;
+            particle.pos = particle.pos + Vec2{x: particle.velocity.x * dt, y: particle.velocity.y * dt};
+            particle.age += dt;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.pool.clear();
+    }
+
+    /// everything goes on the one material, they fade out as they age
+    pub fn draw(&self, render_handle: &mut RenderInformation, material: &mut Material, camera: &Camera) {
+        for particle in self.pool.iter().filter(|p| p.is_alive()) {
+            let (r, g, b, a) = particle.colour;
+            let half = particle.size / 2.0;
+            let (pos, size) = camera.rect(
+                Vec2{x: particle.pos.x - half, y: particle.pos.y - half},
+                Vec2{x: particle.size, y: particle.size},
+            );
+            material.add_rectangle(pos, size, Colour::from_rgba(r, g, b, a * particle.life_left()), render_handle);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn particles() -> Particles {
+        Particles {
+            pool: Vec::new(),
+            emitters: Emitters::load(),
+            random: StdRng::seed_from_u64(7),
+        }
+    }
+
+    fn still_particle(speed_kept: f32, gravity: f32) -> Particle {
+        Particle {
+            pos: Vec2{x: 0.0, y: 0.0},
+            velocity: Vec2{x: 100.0, y: 0.0},
+            size: 4.0,
+            colour: (255.0, 255.0, 255.0, 255.0),
+            speed_kept,
+            gravity,
+            lifetime: 1.0,
+            age: 0.0,
+        }
+    }
+
+    #[test]
+    fn built_in_emitters_load() {
+        let emitters = Emitters::load();
+        assert_eq!(emitters.butter_splat.count, 10);
+        assert_eq!(emitters.flour_puff.lifetime, (0.6, 1.1));
+        assert_eq!(emitters.sparkle.speed_kept, 0.01);
+        assert_eq!(emitters.pickup_glow.gravity, -60.0);
+    }
+
+    #[test]
+    fn missing_keys_stay_blank() {
+        let source = "emitter butter_splat\ncount 3\nemitter flour_puff\nemitter sparkle\nemitter pickup_glow";
+        let emitters = Emitters::parse(source).unwrap();
+        assert_eq!(emitters.butter_splat, Emitter{count: 3, ..Emitter::BLANK});
+        assert_eq!(emitters.sparkle, Emitter::BLANK);
+    }
+
+    #[test]
+    fn bad_emitter_files_are_rejected() {
+        let all = "emitter butter_splat\nemitter flour_puff\nemitter sparkle\nemitter pickup_glow\n";
+        let cases = [
+            ("count 3\n", "line 1: `count` before any emitter"),
+            ("emitter butter_splat\nsped 1 2", "line 2: unknown keyword `sped`"),
+            ("emitter butter_splat\nspeed 1", "line 2: "),
+            ("emitter butter_splat\nsize 8 2", "line 2: 8 is bigger than 2"),
+            ("emitter butter_splat\ncolour 1 2 x 4", "line 2: "),
+            ("emitter flour_puff\nemitter sparkle\nemitter pickup_glow", "missing emitter `butter_splat`"),
+        ];
+        for (source, expected) in cases {
+            let error = Emitters::parse(source).unwrap_err();
+            assert!(error.starts_with(expected), "{:?} gave {:?}", source, error);
+        }
+
+        assert_eq!(Emitters::parse(&format!("{}emitter butter", all)).unwrap_err(), "unknown emitter `butter`");
+        assert_eq!(Emitters::parse(&format!("{}emitter sparkle", all)).unwrap_err(), "line 5: emitter `sparkle` given twice");
+    }
+
+    #[test]
+    fn emit_stops_at_max_particles() {
+        let mut particles = particles();
+        let emitter = Emitter{count: 300, lifetime: (1.0, 1.0), ..Emitter::BLANK};
+        particles.emit(&emitter, Vec2{x: 0.0, y: 0.0});
+        particles.emit(&emitter, Vec2{x: 0.0, y: 0.0});
+        assert_eq!(particles.pool.len(), MAX_PARTICLES);
+    }
+
+    #[test]
+    fn dead_particles_get_reused() {
+        let mut particles = particles();
+        let emitter = Emitter{count: 20, lifetime: (0.5, 0.5), ..Emitter::BLANK};
+        particles.emit(&emitter, Vec2{x: 0.0, y: 0.0});
+        particles.update(1.0);
+        assert!(particles.pool.iter().all(|p| !p.is_alive()));
+
+        particles.emit(&emitter, Vec2{x: 0.0, y: 0.0});
+        assert_eq!(particles.pool.len(), 20);
+        assert!(particles.pool.iter().all(|p| p.is_alive()));
+    }
+
+    #[test]
+    fn update_slows_pulls_and_ages() {
+        let mut particles = particles();
+        particles.pool.push(still_particle(0.25, 10.0));
+        particles.update(0.5);
+
+        let particle = particles.pool[0];
+        // 0.25 kept over a second is 0.5 kept over half of one
+        assert!((particle.velocity.x - 50.0).abs() < 1e-3);
+        assert!((particle.velocity.y - 5.0).abs() < 1e-3);
+        assert!((particle.pos.x - 25.0).abs() < 1e-3);
+        assert!((particle.pos.y - 2.5).abs() < 1e-3);
+        assert_eq!(particle.age, 0.5);
+    }
+
+    #[test]
+    fn dead_particles_stop_moving() {
+        let mut particles = particles();
+        particles.pool.push(Particle{age: 1.0, ..still_particle(1.0, 0.0)});
+        particles.update(0.5);
+        assert_eq!(particles.pool[0].pos.x, 0.0);
+        assert_eq!(particles.pool[0].age, 1.0);
+    }
+}
@@ -0,0 +1,79 @@
+/// Perks change what reflected butter does when it reaches a chef, they're
+/// picked between waves and every extra copy makes the effect stronger.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Perk {
+    Pierce,
+    Explosive,
+    Chain,
+    Grease,
+}
+
+impl Perk {
+    pub const ALL: [Self; 4] = [Self::Pierce, Self::Explosive, Self::Chain, Self::Grease];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Pierce => "Piercing Butter",
+            Self::Explosive => "Explosive Butter",
+            Self::Chain => "Chain Butter",
+            Self::Grease => "Greasy Butter",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Pierce => "Goes through one more chef",
+            Self::Explosive => "Bursts and hurts nearby chefs",
+            Self::Chain => "Bounces to one more nearby chef",
+            Self::Grease => "Leaves grease that slows chefs",
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PerkSet {
+    stacks: [u32; 4],
+}
+
+impl PerkSet {
+    pub fn add(&mut self, perk: Perk) {
+        self.stacks[perk.index()] += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.stacks = [0; 4];
+    }
+
+    pub fn stacks(&self, perk: Perk) -> u32 {
+        self.stacks[perk.index()]
+    }
+
+    /// what a piece of butter reflected right now should be able to do
+    pub fn butter_effects(&self) -> ButterEffects {
+        let explosive = self.stacks(Perk::Explosive);
+        let grease = self.stacks(Perk::Grease);
+
+        ButterEffects {
+            pierce: self.stacks(Perk::Pierce),
+            explosion_radius: if explosive > 0 { 40.0 + 30.0 * explosive as f32 } else { 0.0 },
+            chains: self.stacks(Perk::Chain),
+            grease_time: 5.0 * grease as f32,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ButterEffects {
+    /// chefs the butter can pass through before it stops
+    pub pierce: u32,
+    /// 0 means no explosion
+    pub explosion_radius: f32,
+    /// times the butter can jump to another chef after a hit
+    pub chains: u32,
+    /// how long grease puddles last, 0 means no grease
+    pub grease_time: f32,
+}
@@ -0,0 +1,143 @@
+use bottomless_pit::colour::Colour;
+use bottomless_pit::engine_handle::Engine;
+use bottomless_pit::material::{Material, MaterialBuilder};
+use bottomless_pit::render::RenderInformation;
+use bottomless_pit::texture::Texture;
+use bottomless_pit::vectors::Vec2;
+
+use crate::camera::Camera;
+
+const PICKUP_SIZE: f32 = 30.0;
+const PICKUP_LIFETIME: f32 = 10.0;
+/// pickups start blinking when they're about to vanish
+const PICKUP_BLINK_TIME: f32 = 3.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PickupKind {
+    Heart,
+    RapidCharge,
+    Shield,
+    Magnet,
+}
+
+impl PickupKind {
+    pub const ALL: [Self; 4] = [Self::Heart, Self::RapidCharge, Self::Shield, Self::Magnet];
+
+    pub fn create_materials(engine_handle: &mut Engine) -> [Material; 4] {
+        Self::ALL.map(|kind| {
+            let texture = Texture::new(engine_handle, kind.texture_path());
+            MaterialBuilder::new().add_texture(texture).build(engine_handle)
+        })
+    }
+
+    fn texture_path(&self) -> &'static str {
+        match self {
+            Self::Heart => "assets/pickupHeart.png",
+            Self::RapidCharge => "assets/pickupCharge.png",
+            Self::Shield => "assets/pickupShield.png",
+            Self::Magnet => "assets/pickupMagnet.png",
+        }
+    }
+
+    /// how long the effect lasts once collected, hearts are instant
+    pub fn duration(&self) -> f32 {
+        match self {
+            Self::Heart => 0.0,
+            Self::RapidCharge => 8.0,
+            Self::Shield => 10.0,
+            Self::Magnet => 8.0,
+        }
+    }
+
+    /// for the burst when it's picked up, roughly what the icon looks like
+    pub fn colour(&self) -> (f32, f32, f32, f32) {
+        match self {
+            Self::Heart => (235.0, 60.0, 70.0, 255.0),
+            Self::RapidCharge => (255.0, 170.0, 40.0, 255.0),
+            Self::Shield => (90.0, 170.0, 255.0, 255.0),
+            Self::Magnet => (200.0, 200.0, 210.0, 255.0),
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    pub pos: Vec2<f32>,
+    pub size: Vec2<f32>,
+    lifetime: f32,
+}
+
+impl Pickup {
+    pub fn new(kind: PickupKind, center: Vec2<f32>) -> Self {
+        Self {
+            kind,
+            pos: Vec2{x: center.x - PICKUP_SIZE / 2.0, y: center.y - PICKUP_SIZE / 2.0},
+            size: Vec2{x: PICKUP_SIZE, y: PICKUP_SIZE},
+            lifetime: PICKUP_LIFETIME,
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.lifetime -= dt;
+    }
+
+    pub fn collect(&mut self) {
+        self.lifetime = 0.0;
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.lifetime <= 0.0
+    }
+
+    pub fn draw(&self, render_handle: &mut RenderInformation, materials: &mut [Material], camera: &Camera) {
+        let blink_off = self.lifetime < PICKUP_BLINK_TIME && (self.lifetime * 4.0).fract() < 0.5;
+        if blink_off {
+            return;
+        }
+
+        let (pos, size) = camera.rect(self.pos, self.size);
+        materials[self.kind.index()].add_rectangle(pos, size, Colour::WHITE, render_handle);
+    }
+}
+
+/// Timers for the pickups the player is currently powered up by
+#[derive(Clone, Debug, Default)]
+pub struct Effects {
+    remaining: [f32; 4],
+}
+
+impl Effects {
+    pub fn update(&mut self, dt: f32) {
+        self.remaining.iter_mut().for_each(|t| *t = (*t - dt).max(0.0));
+    }
+
+    pub fn start(&mut self, kind: PickupKind) {
+        self.remaining[kind.index()] = kind.duration();
+    }
+
+    pub fn end(&mut self, kind: PickupKind) {
+        self.remaining[kind.index()] = 0.0;
+    }
+
+    pub fn clear(&mut self) {
+        self.remaining = [0.0; 4];
+    }
+
+    pub fn is_active(&self, kind: PickupKind) -> bool {
+        self.remaining[kind.index()] > 0.0
+    }
+
+    /// every running effect with how much of it is left from 1 to 0
+    pub fn active(&self) -> Vec<(PickupKind, f32)> {
+        PickupKind::ALL
+            .iter()
+            .filter(|k| self.is_active(**k))
+            .map(|k| (*k, self.remaining[k.index()] / k.duration()))
+            .collect()
+    }
+}
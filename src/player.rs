@@ -1,5 +1,4 @@
 use std::f32::consts::PI;
-use web_time::Instant;
 
 use bottomless_pit::colour::Colour;
 use bottomless_pit::engine_handle::Engine;
@@ -10,112 +9,272 @@ use bottomless_pit::texture::Texture;
 use bottomless_pit::vectors::Vec2;
 
 use crate::enemy::Butter;
-use crate::animation::Anmiation;
+use crate::event::{GameEvent, HitTarget};
+use crate::animation::{AnimationClock, Anmiation};
+use crate::arena::Arena;
+use crate::camera::Camera;
+use crate::difficulty::Difficulty;
+use crate::perk::{Perk, PerkSet};
+use crate::pickup::{Effects, PickupKind};
+use crate::upgrade::{Modifier, Stat, Stats, Upgrade};
 use crate::{collision, move_towards};
 
+const WEAPON_SIZE: Vec2<f32> = Vec2{x: 75.0, y: 120.0};
+const RAPID_CHARGE_SPEED: f32 = 2.0;
+/// letting go any earlier than this doesn't swing at all
+pub const MIN_SWING_CHARGE: f32 = 0.2;
+/// seconds before the butter would have hit us for a parry to count as perfect
+const PERFECT_PARRY_TIME: f32 = 0.35;
+const MAGNET_RADIUS: f32 = 220.0;
+const MAGNET_STRENGTH: f32 = 3.0;
+/// how far apart the rows of hearts are when there are two players
+pub const HEART_ROW_HEIGHT: f32 = 55.0;
+
+/// how quickly we can change direction while sliding on butter, per second
+const SLIDE_GRIP: f32 = 1.5;
+/// how far in front of a keyboard player their pin points
+const KEYBOARD_AIM_DISTANCE: f32 = 200.0;
+/// seconds a partner has to stand next to a downed player to get them back up
+const REVIVE_TIME: f32 = 3.0;
+
+/// the body sheets in `PlayerAnmiationState::index` order, each is a file, how many frames
+/// it has and the seconds per frame. Players keep a clock per row and share the sheets
+const BODY_SHEETS: [(&str, usize, f32); 7] = [
+    ("assets/idleUp.png", 4, 1.0/6.0),
+    ("assets/idle.png", 4, 1.0/6.0),
+    ("assets/idleSide.png", 4, 1.0/6.0),
+    ("assets/walkUp.png", 6, 1.0/6.0),
+    ("assets/walk.png", 6, 1.0/6.0),
+    ("assets/walkSide.png", 6, 1.0/6.0),
+    ("assets/death.png", 1, 1.0/6.0),
+];
+/// the rolling pin resting, winding up and swinging, these play once and hold their last frame
+const PIN_SHEETS: [(&str, usize, f32); 3] = [
+    ("assets/pinIdle.png", 1, 100.0),
+    ("assets/pinCharge.png", 3, 1.0/7.0),
+    ("assets/pinSwing.png", 4, 1.0/6.0),
+];
+
+/// What a player wants to do for one update. It's plain data so it can come from
+/// this machine's keyboard and mouse or from the other side of an online game
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PlayerInput {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub charging: bool,
+    pub released: bool,
+    /// where the pin points, `None` points it the way we last walked
+    pub aim: Option<Vec2<f32>>,
+}
+
+/// Which inputs drive a player, the second player in co-op gets a corner of the keyboard
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Controls {
+    /// W A S D to move, the mouse aims and charges
+    Mouse,
+    /// I J K L to move, the pin points the way we last walked and U charges
+    Keyboard,
+    /// someone on another machine, their inputs come in over the network
+    Remote,
+}
+
+impl Controls {
+    pub fn read(&self, engine_handle: &Engine) -> PlayerInput {
+        let ([up, down, left, right], charge) = match self {
+            Self::Mouse => ([Key::W, Key::S, Key::A, Key::D], None),
+            Self::Keyboard => ([Key::I, Key::K, Key::J, Key::L], Some(Key::U)),
+            Self::Remote => return PlayerInput::default(),
+        };
+
+        let (charging, released, aim) = match charge {
+            Some(key) => (engine_handle.is_key_down(key), engine_handle.is_key_released(key), None),
+            None => (
+                engine_handle.is_mouse_key_down(MouseKey::Left),
+                engine_handle.is_mouse_key_released(MouseKey::Left),
+                Some(engine_handle.get_mouse_position()),
+            ),
+        };
+
+        PlayerInput {
+            up: engine_handle.is_key_down(up),
+            down: engine_handle.is_key_down(down),
+            left: engine_handle.is_key_down(left),
+            right: engine_handle.is_key_down(right),
+            charging,
+            released,
+            aim,
+        }
+    }
+}
 
+#[derive(Clone, Debug)]
 pub struct Player {
     pub pos: Vec2<f32>,
     pub size: Vec2<f32>,
+    spawn: Vec2<f32>,
+    /// 0 for player one and 1 for player two, picks our row of hearts and our colour
+    slot: usize,
+    controls: Controls,
+    /// the way a keyboard player last walked, it's where their pin points
+    facing: Vec2<f32>,
+    revive_timer: f32,
+    velocity: Vec2<f32>,
     hp: u8,
-    max_hp: u8,
-    charge_timer: Option<Instant>,
+    stats: Stats,
+    upgrades: Vec<Upgrade>,
+    perks: PerkSet,
+    effects: Effects,
+    /// seconds the pin has been held back for
+    charge_timer: Option<f32>,
     weapon_pos: Vec2<f32>,
     weapon_size: Vec2<f32>,
     animation_state: PlayerAnmiationState,
-    animations: [Anmiation; 7],
-    attack_animations: [Anmiation; 3],
+    animations: [AnimationClock; 7],
+    attack_animations: [AnimationClock; 3],
     current_attack_animation: usize,
+    rotation: f32,
+}
+
+/// The sprite sheets and hearts every player draws with. Whoever owns the players owns one
+/// of these, the same way the level holds the chef animations
+pub struct PlayerSprites {
+    body: [Anmiation; 7],
+    pin: [Anmiation; 3],
     full_heart: Material,
     empty_heart: Material,
-    rotation: f32,
 }
 
-impl Player {
-    pub fn new(pos: Vec2<f32>, engine_handle: &mut Engine) -> Self {
+impl PlayerSprites {
+    pub fn new(engine_handle: &mut Engine) -> Self {
         let full_heart_tex = Texture::new(engine_handle, "assets/heart.png");
         let empty_heart_text = Texture::new(engine_handle, "assets/heartEmpty.png");
-        let full_heart = MaterialBuilder::new().add_texture(full_heart_tex).build(engine_handle);
-        let empty_heart = MaterialBuilder::new().add_texture(empty_heart_text).build(engine_handle);
 
+        Self {
+            body: BODY_SHEETS.map(|(path, frames, frame_time)| Anmiation::new(path, Vec2{x: 100.0, y: 100.0}, frames, frame_time, true, engine_handle)),
+            pin: PIN_SHEETS.map(|(path, frames, frame_time)| Anmiation::new(path, Vec2{x: 100.0, y: 160.0}, frames, frame_time, false, engine_handle)),
+            full_heart: MaterialBuilder::new().add_texture(full_heart_tex).build(engine_handle),
+            empty_heart: MaterialBuilder::new().add_texture(empty_heart_text).build(engine_handle),
+        }
+    }
+
+    /// once a frame after every player has been added with `Player::draw`
+    pub fn draw<'p, 'o>(&'o mut self, render_handle: &mut RenderInformation<'p, 'o>) where 'o: 'p {
+        self.body.iter_mut().for_each(|a| a.draw(render_handle));
+        self.pin.iter_mut().for_each(|a| a.draw(render_handle));
+        self.empty_heart.draw(render_handle);
+        self.full_heart.draw(render_handle);
+    }
+}
+
+impl Player {
+    pub fn new(pos: Vec2<f32>, slot: usize, controls: Controls) -> Self {
         Self {
             pos,
             size: Vec2 {x: 50.0, y: 50.0},
+            spawn: pos,
+            slot,
+            controls,
+            facing: Vec2 {x: 0.0, y: 1.0},
+            revive_timer: 0.0,
+            velocity: Vec2 {x: 0.0, y: 0.0},
             hp: 3,
-            max_hp: 3,
+            stats: Stats::new(),
+            upgrades: Vec::new(),
+            perks: PerkSet::default(),
+            effects: Effects::default(),
             charge_timer: None,
             weapon_pos: Vec2 {x: 0.0, y: 0.0},
-            weapon_size: Vec2 {x: 75.0, y: 120.0},
+            weapon_size: WEAPON_SIZE,
             animation_state: PlayerAnmiationState::IdleDown,
-            animations: Self::create_animations(engine_handle),
-            attack_animations: Self::create_attack_animations(engine_handle),
+            animations: BODY_SHEETS.map(|(_, frames, frame_time)| AnimationClock::new(frames, frame_time, true)),
+            attack_animations: PIN_SHEETS.map(|(_, frames, frame_time)| AnimationClock::new(frames, frame_time, false)),
             current_attack_animation: 0,
-            full_heart,
-            empty_heart,
             rotation: 0.0,
         }
     }
 
-    fn create_animations(engine_handle: &mut Engine) -> [Anmiation; 7] {
-        [
-            Anmiation::new("assets/idleUp.png", Vec2{x: 100.0, y: 100.0}, 4, 1.0/6.0, true, engine_handle),
-            Anmiation::new("assets/idle.png", Vec2{x: 100.0, y: 100.0}, 4, 1.0/6.0, true, engine_handle),
-            Anmiation::new("assets/idleSide.png", Vec2{x: 100.0, y: 100.0}, 4, 1.0/6.0, true, engine_handle),
-            Anmiation::new("assets/walkUp.png", Vec2{x: 100.0, y: 100.0}, 6, 1.0/6.0, true, engine_handle),
-            Anmiation::new("assets/walk.png", Vec2{x: 100.0, y: 100.0}, 6, 1.0/6.0, true, engine_handle),
-            Anmiation::new("assets/walkSide.png", Vec2{x: 100.0, y: 100.0}, 6, 1.0/6.0, true, engine_handle),
-            Anmiation::new("assets/death.png", Vec2{x: 100.0, y: 100.0}, 1, 1.0/6.0, true, engine_handle),
-        ]
-    }
-
-    fn create_attack_animations(engine_handle: &mut Engine) -> [Anmiation; 3] {
-        [
-            Anmiation::new("assets/pinIdle.png", Vec2{x: 100.0, y: 160.0}, 1, 100.0, false, engine_handle),
-            Anmiation::new("assets/pinCharge.png", Vec2{x: 100.0, y: 160.0}, 3, 1.0/7.0, false, engine_handle),
-            Anmiation::new("assets/pinSwing.png", Vec2{x: 100.0, y: 160.0}, 4, 1.0/6.0, false, engine_handle),
-        ]
-    }
-
-    pub fn draw<'p, 'o>(&'o mut self, render_handle: &mut RenderInformation<'p, 'o>) where 'o: 'p {
+    /// the hearts are hud and stay put, only we and the pin go through the camera. Nothing
+    /// shows up until `sprites` gets drawn
+    pub fn draw(&self, render_handle: &RenderInformation, sprites: &mut PlayerSprites, camera: &Camera) {
         let (index, flipped) = self.animation_state.index();
-        self.animations[index].add_instance(render_handle, self.pos, self.size, flipped);
+        let (pos, size) = camera.rect(self.pos, self.size);
+        let frame = self.animations[index].get_frame();
+        sprites.body[index].add_tinted_instance_at_frame(render_handle, pos, size, flipped, frame, self.tint());
 
         if !self.is_dead() {
-            self.attack_animations[self.current_attack_animation].add_with_rotation(render_handle, self.weapon_pos, self.weapon_size, false, self.rotation);
+            let (weapon_pos, weapon_size) = camera.rect(self.weapon_pos, self.weapon_size);
+            let pin = self.current_attack_animation;
+            let frame = self.attack_animations[pin].get_frame();
+            sprites.pin[pin].add_still(render_handle, weapon_pos, weapon_size, frame, self.rotation, Colour::WHITE);
         }
 
+        let hud = camera.get_viewport();
         let mut offset = 0;
         let step = 75;
-        let max = self.max_hp as u32 * step;
+        let max = self.max_hp() as u32 * step;
+        let y = self.slot as f32 * HEART_ROW_HEIGHT;
+        let tint = self.tint();
         for _ in 0..self.hp {
-            self.full_heart.add_rectangle(Vec2{x: offset as f32, y: 0.0}, Vec2{x: 50.0, y: 50.0}, Colour::WHITE, &render_handle);
+            let (pos, size) = hud.rect(Vec2{x: offset as f32, y}, Vec2{x: 50.0, y: 50.0});
+            sprites.full_heart.add_rectangle(pos, size, tint, render_handle);
             offset += step;
         }
         for _ in (offset..max).step_by(step as usize) {
-            self.empty_heart.add_rectangle(Vec2{x: offset as f32, y: 0.0}, Vec2{x: 50.0, y: 50.0}, Colour::WHITE, &render_handle);
+            let (pos, size) = hud.rect(Vec2{x: offset as f32, y}, Vec2{x: 50.0, y: 50.0});
+            sprites.empty_heart.add_rectangle(pos, size, tint, render_handle);
             offset += step;
         }
+    }
 
-        self.animations[index].draw(render_handle);
-        self.attack_animations[self.current_attack_animation].draw(render_handle);
-        self.empty_heart.draw(render_handle);
-        self.full_heart.draw(render_handle);
+    pub fn read_input(&self, engine_handle: &Engine) -> PlayerInput {
+        self.controls.read(engine_handle)
+    }
+
+    pub fn get_controls(&self) -> Controls {
+        self.controls
     }
 
-    pub fn update(&mut self, engine_handle: &mut Engine, dt: f32, butters: &mut Vec<Butter>) {
+    /// where they stand at the start of the next run
+    pub fn set_spawn(&mut self, spawn: Vec2<f32>) {
+        self.spawn = spawn;
+    }
+
+    /// player two is a bit blue so you can tell the biscuits apart
+    fn tint(&self) -> Colour {
+        if self.slot == 0 {
+            Colour::WHITE
+        } else {
+            Colour::from_rgba(170.0, 200.0, 255.0, 255.0)
+        }
+    }
+
+    pub fn update(&mut self, input: &PlayerInput, dt: f32, butters: &mut Vec<Butter>, arena: &Arena, events: &mut Vec<GameEvent>) {
         if self.is_dead() {
             self.animation_state = PlayerAnmiationState::Dead;
             return;
         }
 
-        let movment_factor = 40.0 * dt;
-        let mouse_pos = engine_handle.get_mouse_position();
+        let movment_factor = self.stats.get(Stat::MoveSpeed);
+        let aim_pos = self.aim_pos(input);
         let animation_at_start = self.animation_state;
         let attack_animation_start = self.current_attack_animation;
         
-        self.weapon_pos = move_towards(self.get_center(), mouse_pos, 40.0);
+        self.weapon_pos = move_towards(self.get_center(), aim_pos, 40.0);
         self.weapon_pos = self.weapon_pos - Vec2{x: self.size.x/2.0, y: self.size.y/2.0};
-        self.rotoate_weapon(mouse_pos);
+        self.rotoate_weapon(aim_pos);
+        let reach = self.stats.get(Stat::Reach);
+        self.weapon_size = Vec2{x: WEAPON_SIZE.x * reach, y: WEAPON_SIZE.y * reach};
+
+        self.effects.update(dt);
+        if self.effects.is_active(PickupKind::Magnet) {
+            let pin = self.get_weapon_center();
+            butters
+                .iter_mut()
+                .filter(|b| !b.is_reflected())
+                .for_each(|b| b.pull_towards(pin, MAGNET_RADIUS, MAGNET_STRENGTH, dt));
+        }
 
         // cope freyhoe also 0 = straight up
         let player_dir: u8 = 1 * u8::from(self.rotation > 225.0 && self.rotation <= 315.0) + // down
@@ -126,55 +285,73 @@ impl Player {
 
         let mut vel = Vec2{x: 0.0, y: 0.0};
 
-        if engine_handle.is_key_down(Key::W) {
+        if input.up {
             vel.y -= movment_factor;
         }
 
-        if engine_handle.is_key_down(Key::S) {
+        if input.down {
             vel.y += movment_factor;
         }
 
-        if engine_handle.is_key_down(Key::A) {
+        if input.left {
             vel.x -= movment_factor;
         }
 
-        if engine_handle.is_key_down(Key::D) {
+        if input.right {
             vel.x += movment_factor;
         }
 
         if vel.x != 0.0 || vel.y != 0.0 {
-            self.pos = self.pos + vel;
             self.animation_state = PlayerAnmiationState::walking_from_dir(player_dir);
+            let speed = (vel.x.powi(2) + vel.y.powi(2)).sqrt();
+            self.facing = Vec2{x: vel.x / speed, y: vel.y / speed};
+        }
+
+        // butter on the floor means we keep our momentum and only slowly steer
+        let (hit_box_pos, hit_box_size) = self.get_hit_box();
+        if arena.hazards.is_slippery(hit_box_pos, hit_box_size) {
+            let grip = (SLIDE_GRIP * dt).min(1.0);
+            self.velocity.x += (vel.x - self.velocity.x) * grip;
+            self.velocity.y += (vel.y - self.velocity.y) * grip;
+        } else {
+            self.velocity = vel;
         }
+        self.pos = self.pos + Vec2{x: self.velocity.x * dt, y: self.velocity.y * dt};
 
-        if self.pos.x > 800.0 - self.size.x {
-            self.pos.x = 800.0 - self.size.x;
+        // only our feet collide so we can stand in front of tall things
+        let (hit_box_pos, hit_box_size) = self.get_hit_box();
+        let pushed = arena.push_out(hit_box_pos, hit_box_size);
+        self.pos = self.pos + (pushed - hit_box_pos);
+
+        if self.pos.x > arena.size.x - self.size.x {
+            self.pos.x = arena.size.x - self.size.x;
         } else if self.pos.x < 0.0 {
             self.pos.x = 0.0;
         }
 
-        if self.pos.y > 800.0 - self.size.y {
-            self.pos.y = 800.0 - self.size.y
+        if self.pos.y > arena.size.y - self.size.y {
+            self.pos.y = arena.size.y - self.size.y
         } else if self.pos.y < 0.0 {
             self.pos.y = 0.0;
         }
 
-        if engine_handle.is_mouse_key_down(MouseKey::Left) && self.attack_animations[self.current_attack_animation].is_done() {
+        if let Some(time) = self.charge_timer.as_mut() {
+            *time += dt;
+        }
+
+        if input.charging && self.attack_animations[self.current_attack_animation].is_done() {
             match self.charge_timer {
                 Some(_) => {},
-                None => self.charge_timer = Some(Instant::now())
+                None => {
+                    self.charge_timer = Some(0.0);
+                    events.push(GameEvent::ParryCharged);
+                },
             }
             self.current_attack_animation = 1;
-        } else if engine_handle.is_mouse_key_released(MouseKey::Left) {
-            match self.charge_timer {
-                Some(time) => {
-                    let charge_time = time.elapsed().as_secs_f32();
-
-                    if charge_time > 0.2 {
-                        self.charge_attack(charge_time, butters, mouse_pos);
-                    }
-                },
-                None => {},
+        } else if input.released {
+            if let Some(charge_time) = self.get_charge().filter(|c| *c > MIN_SWING_CHARGE) {
+                events.push(GameEvent::ParrySwung);
+                self.charge_attack(charge_time, butters, aim_pos, events);
             }
 
             self.charge_timer = None;
@@ -192,25 +369,63 @@ impl Player {
             self.attack_animations[attack_animation_start].reset();
         }
 
-
         let (index, _) = animation_at_start.index();
         self.animations[index].update(dt);
         self.attack_animations[self.current_attack_animation].update(dt);
     }
 
-    pub fn restart(&mut self) {
-        self.hp = 3;
-        self.max_hp = 3;
-        self.pos = Vec2{x: 400.0, y: 400.0};
+    pub fn restart(&mut self, difficulty: Difficulty, hearts: f32) {
+        self.stats.clear();
+        self.stats.add_modifier(Modifier::Add(Stat::MaxHp, hearts - 3.0));
+        self.stats.add_modifier(Modifier::Multiply(Stat::ReflectWindow, difficulty.reflect_window()));
+        self.upgrades.clear();
+        self.perks.clear();
+        self.effects.clear();
+        self.hp = self.max_hp();
+        self.pos = self.spawn;
+        self.velocity = Vec2{x: 0.0, y: 0.0};
+        self.facing = Vec2{x: 0.0, y: 1.0};
+        self.revive_timer = 0.0;
+        self.charge_timer = None;
+    }
+
+    fn aim_pos(&self, input: &PlayerInput) -> Vec2<f32> {
+        match input.aim {
+            Some(aim) => aim,
+            None => {
+                let center = self.get_center();
+                Vec2{x: center.x + self.facing.x * KEYBOARD_AIM_DISTANCE, y: center.y + self.facing.y * KEYBOARD_AIM_DISTANCE}
+            },
+        }
     }
 
-    fn charge_attack(&mut self, charge_time: f32, butters: &mut Vec<Butter>, mouse_pos: Vec2<f32>) {
+    fn charge_attack(&mut self, charge_time: f32, butters: &mut Vec<Butter>, mouse_pos: Vec2<f32>, events: &mut Vec<GameEvent>) {
+        let damage_multiplier = self.stats.get(Stat::ReflectDamage);
+        let effects = self.perks.butter_effects();
+        let center = self.get_center();
+
+        // the catch area grows or shrinks around the pin's middle
+        let window = self.stats.get(Stat::ReflectWindow);
+        let window_size = Vec2{x: self.weapon_size.x * window, y: self.weapon_size.y * window};
+        let window_pos = Vec2{
+            x: self.weapon_pos.x - (window_size.x - self.weapon_size.x) / 2.0,
+            y: self.weapon_pos.y - (window_size.y - self.weapon_size.y) / 2.0,
+        };
+
         // reflect bullets
         butters
             .iter_mut()
-            .filter(|b| !b.is_reflected() && collision::rect_rect(b.size, b.pos, self.weapon_size, self.weapon_pos))
+            .filter(|b| !b.is_reflected() && collision::rect_rect(b.size, b.pos, window_size, window_pos))
             .for_each(|b| {
-                b.change_target(mouse_pos, charge_time);
+                // it's perfect if the butter was about to hit us anyway
+                let butter_center = b.get_center();
+                let distance = ((butter_center.x - center.x).powi(2) + (butter_center.y - center.y).powi(2)).sqrt();
+                events.push(GameEvent::Reflect{
+                    pos: butter_center,
+                    perfect: distance / b.speed() < PERFECT_PARRY_TIME,
+                });
+
+                b.change_target(mouse_pos, charge_time, damage_multiplier, effects);
             })
     }
 
@@ -226,24 +441,117 @@ impl Player {
         (pos, size)
     }
 
-    pub fn end_wave(&mut self) {
-        self.hp += 1;
-        if self.hp > self.max_hp {
-            self.max_hp += 1;
+    pub fn get_body(&self) -> (Vec2<f32>, Vec2<f32>) {
+        (self.pos, self.size)
+    }
+
+    pub fn apply_upgrade(&mut self, upgrade: Upgrade) {
+        self.upgrades.push(upgrade);
+        self.stats.add_modifier(upgrade.modifier());
+        if upgrade == Upgrade::ExtraHeart {
+            self.heal();
+        }
+    }
+
+    pub fn upgrades_owned(&self, upgrade: Upgrade) -> u32 {
+        self.upgrades.iter().filter(|u| **u == upgrade).count() as u32
+    }
+
+    pub fn add_perk(&mut self, perk: Perk) {
+        self.perks.add(perk);
+    }
+
+    pub fn heal(&mut self) {
+        self.hp = (self.hp + 1).min(self.max_hp());
+    }
+
+    pub fn is_hurt(&self) -> bool {
+        self.hp < self.max_hp()
+    }
+
+    pub fn max_hp(&self) -> u8 {
+        self.stats.get(Stat::MaxHp) as u8
+    }
+
+    pub fn get_weapon_center(&self) -> Vec2<f32> {
+        Vec2{x: self.weapon_pos.x + self.weapon_size.x/2.0, y: self.weapon_pos.y + self.weapon_size.y/2.0}
+    }
+
+    pub fn apply_pickup(&mut self, kind: PickupKind) {
+        match kind {
+            PickupKind::Heart => self.heal(),
+            _ => self.effects.start(kind),
         }
     }
 
+    pub fn get_hp(&self) -> u8 {
+        self.hp
+    }
+
+    /// how long the current wind up counts for with charge speed taken into account,
+    /// None when they aren't winding up
+    pub fn get_charge(&self) -> Option<f32> {
+        let mut charge_time = self.charge_timer? * self.stats.get(Stat::ChargeSpeed);
+        if self.effects.is_active(PickupKind::RapidCharge) {
+            charge_time *= RAPID_CHARGE_SPEED;
+        }
+        Some(charge_time)
+    }
+
+    pub fn active_effects(&self) -> Vec<(PickupKind, f32)> {
+        self.effects.active()
+    }
+
     pub fn get_center(&self) -> Vec2<f32> {
         Vec2{x: self.pos.x + self.size.x/2.0, y: self.pos.y + self.size.y/2.0}
     }
 
-    pub fn on_hit(&mut self) {
+    pub fn on_hit(&mut self, events: &mut Vec<GameEvent>) {
+        // already down, two stoves at once shouldn't knock us out twice
+        if self.is_dead() {
+            return;
+        }
+
+        // the bubble pops instead of us
+        if self.effects.is_active(PickupKind::Shield) {
+            self.effects.end(PickupKind::Shield);
+            return;
+        }
+
         self.hp = self.hp.saturating_sub(1);
-    } 
+        events.push(GameEvent::Hit{target: HitTarget::Player(self.slot), pos: self.get_center(), damage: 1.0});
+        if self.is_dead() {
+            events.push(GameEvent::PlayerDied{player: self.slot});
+        }
+    }
 
     pub fn is_dead(&self) -> bool {
         self.hp == 0
     }
+
+    /// counts up while a partner stands next to us and back down when they leave,
+    /// we get back up with one heart once it fills
+    pub fn update_revive(&mut self, dt: f32, partner_near: bool) {
+        if !self.is_dead() {
+            return;
+        }
+
+        if partner_near {
+            self.revive_timer += dt;
+        } else {
+            self.revive_timer = (self.revive_timer - dt).max(0.0);
+        }
+
+        if self.revive_timer >= REVIVE_TIME {
+            self.revive_timer = 0.0;
+            self.hp = 1;
+        }
+    }
+
+    /// how far along getting back up we are, 0 to 1
+    pub fn revive_progress(&self) -> f32 {
+        self.revive_timer / REVIVE_TIME
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -293,4 +601,56 @@ impl PlayerAnmiationState {
             Self::Dead => (6, false),
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(slot: usize) -> Player {
+        Player::new(Vec2{x: 100.0, y: 100.0}, slot, Controls::Remote)
+    }
+
+    #[test]
+    fn dies_after_the_last_heart() {
+        let mut player = player(1);
+        let center = player.get_center();
+        let hit = GameEvent::Hit{target: HitTarget::Player(1), pos: center, damage: 1.0};
+        let mut events = Vec::new();
+
+        for _ in 0..4 {
+            player.on_hit(&mut events);
+        }
+
+        assert_eq!(events, vec![hit, hit, hit, GameEvent::PlayerDied{player: 1}]);
+        assert!(player.is_dead());
+    }
+
+    #[test]
+    fn shield_takes_the_hit() {
+        let mut player = player(0);
+        player.apply_pickup(PickupKind::Shield);
+        let mut events = Vec::new();
+
+        player.on_hit(&mut events);
+        assert!(events.is_empty());
+        assert_eq!(player.get_hp(), 3);
+
+        player.on_hit(&mut events);
+        assert_eq!(player.get_hp(), 2);
+        assert_eq!(events.len(), 1);
+    }
+
+    #[test]
+    fn revives_with_one_heart() {
+        let mut player = player(0);
+        let mut events = Vec::new();
+        (0..3).for_each(|_| player.on_hit(&mut events));
+
+        player.update_revive(REVIVE_TIME / 2.0, true);
+        player.update_revive(REVIVE_TIME / 4.0, false);
+        assert!(player.is_dead());
+        player.update_revive(REVIVE_TIME * 0.8, true);
+        assert_eq!(player.get_hp(), 1);
+    }
+}
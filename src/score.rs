@@ -0,0 +1,100 @@
+const REFLECT_POINTS: u32 = 10;
+const PERFECT_PARRY_POINTS: u32 = 50;
+const REFLECT_KILL_POINTS: u32 = 100;
+/// extra points for every chef after the first that one butter takes out
+const MULTI_KILL_POINTS: u32 = 150;
+const NO_DAMAGE_WAVE_POINTS: u32 = 500;
+
+const KILL_COMBO: f32 = 0.25;
+const PERFECT_PARRY_COMBO: f32 = 0.5;
+const MAX_COMBO: f32 = 5.0;
+/// seconds without a kill or parry before the combo starts draining
+const COMBO_GRACE: f32 = 2.0;
+const COMBO_DECAY: f32 = 0.5;
+
+/// Points for the current run and the combo multiplier they're scaled by
+#[derive(Clone, Debug)]
+pub struct Score {
+    points: u32,
+    combo: f32,
+    idle_timer: f32,
+    hurt_this_wave: bool,
+    wave_open: bool,
+}
+
+impl Score {
+    pub fn new() -> Self {
+        Self {
+            points: 0,
+            combo: 1.0,
+            idle_timer: 0.0,
+            hurt_this_wave: false,
+            wave_open: false,
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.idle_timer += dt;
+        if self.idle_timer > COMBO_GRACE {
+            self.combo = (self.combo - COMBO_DECAY * dt).max(1.0);
+        }
+    }
+
+    pub fn reflect(&mut self, perfect: bool) {
+        if perfect {
+            self.add(PERFECT_PARRY_POINTS);
+            self.bump_combo(PERFECT_PARRY_COMBO);
+        } else {
+            self.add(REFLECT_POINTS);
+        }
+    }
+
+    /// `nth` is how many chefs this butter already took out before this one
+    pub fn reflect_kill(&mut self, nth: u32) {
+        let points = if nth == 0 {
+            REFLECT_KILL_POINTS
+        } else {
+            REFLECT_KILL_POINTS + MULTI_KILL_POINTS * nth
+        };
+        self.add(points);
+        self.bump_combo(KILL_COMBO);
+    }
+
+    pub fn player_hit(&mut self) {
+        self.combo = 1.0;
+        self.hurt_this_wave = true;
+    }
+
+    pub fn start_wave(&mut self) {
+        self.hurt_this_wave = false;
+        self.wave_open = true;
+    }
+
+    pub fn wave_cleared(&mut self, wave: u32) {
+        if !self.wave_open {
+            return;
+        }
+
+        self.wave_open = false;
+        if !self.hurt_this_wave {
+            self.add(NO_DAMAGE_WAVE_POINTS * wave);
+        }
+    }
+
+    pub fn points(&self) -> u32 {
+        self.points
+    }
+
+    pub fn combo(&self) -> f32 {
+        self.combo
+    }
+
+    fn add(&mut self, points: u32) {
+        self.points += (points as f32 * self.combo).round() as u32;
+    }
+
+    fn bump_combo(&mut self, amount: f32) {
+        self.combo = (self.combo + amount).min(MAX_COMBO);
+        self.idle_timer = 0.0;
+    }
+}
@@ -0,0 +1,160 @@
+use bottomless_pit::colour::Colour;
+use bottomless_pit::engine_handle::Engine;
+use bottomless_pit::input::MouseKey;
+use bottomless_pit::material::{Material, MaterialBuilder};
+use bottomless_pit::render::RenderInformation;
+use bottomless_pit::vectors::Vec2;
+
+use crate::level::Level;
+use crate::perk::Perk;
+use crate::text::Text;
+use crate::upgrade::Upgrade;
+use crate::viewport::Viewport;
+
+const OFFER_COUNT: usize = 3;
+const PERK_COUNT: usize = 2;
+
+// where the buttons live in `text` (the title is 0 and the perk heading is 4), each offer
+// and perk is a button followed by its description, the perks come after all the offers
+const COINS: usize = 1;
+const NEXT_WAVE: usize = 2;
+const HEAL: usize = 3;
+const FIRST_OFFER: usize = 5;
+
+/// The intermission between waves where coins from kills are spent on upgrades
+/// and one free perk can be picked
+pub struct Shop {
+    offers: Vec<Upgrade>,
+    bought: Vec<bool>,
+    perks: Vec<Perk>,
+    picked_perk: Option<usize>,
+    first_perk: usize,
+    can_heal: bool,
+    text: Vec<Text>,
+    panel: Material,
+    viewport: Viewport,
+}
+
+impl Shop {
+    pub fn new(level: &mut Level, engine_handle: &mut Engine) -> Self {
+        let offers = level.roll_upgrades(OFFER_COUNT);
+        let perks = level.roll_perks(PERK_COUNT);
+
+        let mut title = Text::new(&format!("Wave {} cleared!", level.get_wave()), 45.0, Vec2{x: 0.0, y: 80.0}, Colour::BLACK, engine_handle);
+        title.pos.x = 400.0 - title.size.x as f32 / 2.0;
+        let coins = Text::new("", 30.0, Vec2{x: 100.0, y: 140.0}, Colour::BLACK, engine_handle);
+        let mut next_wave = Text::new("Next wave", 40.0, Vec2{x: 0.0, y: 665.0}, Colour::BLACK, engine_handle);
+        next_wave.pos.x = 400.0 - next_wave.size.x as f32 / 2.0;
+        let can_heal = level.can_heal();
+        let heal_label = if can_heal {
+            format!("Patch up a heart - {} coins", level.heal_cost())
+        } else {
+            "No patching up in this mode".to_string()
+        };
+        let heal = Text::new(&heal_label, 30.0, Vec2{x: 100.0, y: 590.0}, Colour::BLACK, engine_handle);
+        let perk_title = Text::new("Pick a free perk:", 30.0, Vec2{x: 100.0, y: 455.0}, Colour::BLACK, engine_handle);
+
+        let mut text = vec![title, coins, next_wave, heal, perk_title];
+        for (i, upgrade) in offers.iter().enumerate() {
+            let y = 195.0 + i as f32 * 85.0;
+            let label = format!("{} - {} coins", upgrade.name(), level.upgrade_cost(*upgrade));
+            text.push(Text::new(&label, 30.0, Vec2{x: 100.0, y}, Colour::BLACK, engine_handle));
+            text.push(Text::new(upgrade.description(), 22.0, Vec2{x: 110.0, y: y + 42.0}, Colour::BLACK, engine_handle));
+        }
+
+        let first_perk = text.len();
+        for (i, perk) in perks.iter().enumerate() {
+            let x = 100.0 + i as f32 * 310.0;
+            text.push(Text::new(perk.name(), 26.0, Vec2{x, y: 500.0}, Colour::BLACK, engine_handle));
+            text.push(Text::new(perk.description(), 18.0, Vec2{x, y: 540.0}, Colour::BLACK, engine_handle));
+        }
+
+        let mut shop = Self {
+            bought: vec![false; offers.len()],
+            offers,
+            perks,
+            picked_perk: None,
+            first_perk,
+            can_heal,
+            text,
+            panel: MaterialBuilder::new().build(engine_handle),
+            viewport: Viewport::new(),
+        };
+        shop.update_coins(level, engine_handle);
+        shop.set_viewport(level.get_viewport(), engine_handle);
+        shop
+    }
+
+    /// returns true once the player is ready for the next wave
+    pub fn update(&mut self, level: &mut Level, engine_handle: &mut Engine) -> bool {
+        let mouse_pos = self.viewport.to_logical(engine_handle.get_mouse_position());
+        if !engine_handle.is_mouse_key_pressed(MouseKey::Left) {
+            return false;
+        }
+
+        if self.text[NEXT_WAVE].is_clicked(mouse_pos) {
+            return true;
+        }
+
+        if self.text[HEAL].is_clicked(mouse_pos) && level.buy_heal(engine_handle) {
+            self.update_coins(level, engine_handle);
+        }
+
+        for i in 0..self.perks.len() {
+            let button = self.first_perk + i * 2;
+            if self.picked_perk.is_none() && self.text[button].is_clicked(mouse_pos) {
+                level.add_perk(self.perks[i]);
+                self.picked_perk = Some(i);
+                self.text[button].change_text(&format!("{} - taken", self.perks[i].name()), engine_handle);
+            }
+        }
+
+        for i in 0..self.offers.len() {
+            let button = FIRST_OFFER + i * 2;
+            if self.bought[i] || !self.text[button].is_clicked(mouse_pos) {
+                continue;
+            }
+
+            if level.buy_upgrade(self.offers[i], engine_handle) {
+                self.bought[i] = true;
+                self.text[button].change_text(&format!("{} - bought", self.offers[i].name()), engine_handle);
+                self.update_coins(level, engine_handle);
+            }
+        }
+
+        false
+    }
+
+    /// call whenever the window might have changed size
+    pub fn set_viewport(&mut self, viewport: Viewport, engine_handle: &mut Engine) {
+        self.viewport = viewport;
+        self.text.iter_mut().for_each(|t| t.fit(&viewport, engine_handle));
+    }
+
+    pub fn draw<'p, 'o>(&'o mut self, render_handle: &mut RenderInformation<'p, 'o>) where 'o: 'p {
+        let (panel_pos, panel_size) = self.viewport.rect(Vec2{x: 60.0, y: 60.0}, Vec2{x: 680.0, y: 680.0});
+        self.panel.add_rectangle(panel_pos, panel_size, Colour::from_rgba(255.0, 240.0, 210.0, 230.0), render_handle);
+
+        let mut buttons = vec![NEXT_WAVE];
+        if self.can_heal {
+            buttons.push(HEAL);
+        }
+        buttons.extend((0..self.offers.len()).filter(|i| !self.bought[*i]).map(|i| FIRST_OFFER + i * 2));
+        if self.picked_perk.is_none() {
+            buttons.extend((0..self.perks.len()).map(|i| self.first_perk + i * 2));
+        }
+        for button in buttons {
+            let (pos, size) = self.text[button].button_rect();
+            let (pos, size) = self.viewport.rect(pos, size);
+            self.panel.add_rectangle(pos, size, Colour::WHITE, render_handle);
+        }
+
+        self.panel.draw(render_handle);
+        let viewport = self.viewport;
+        self.text.iter_mut().for_each(|t| t.draw(render_handle, &viewport));
+    }
+
+    fn update_coins(&mut self, level: &Level, engine_handle: &mut Engine) {
+        self.text[COINS].change_text(&format!("Coins: {}", level.get_coins()), engine_handle);
+    }
+}
@@ -0,0 +1,147 @@
+use bottomless_pit::vectors::Vec2;
+
+// small steering library for the chefs, everything here works on centers and
+// returns a desired change in velocity that the caller blends together
+
+/// in radians, spreads stacked pairs out so no two of them split along the same line
+const GOLDEN_ANGLE: f32 = 2.399_963;
+
+pub fn arrive(pos: Vec2<f32>, velocity: Vec2<f32>, target: Vec2<f32>, max_speed: f32, slowing_radius: f32) -> Vec2<f32> {
+    let to_target = target - pos;
+    let distance = length(to_target);
+    if distance < 0.001 {
+        return scale(velocity, -1.0);
+    }
+
+    // ease off inside the slowing radius so we don't overshoot and jitter
+    let speed = if distance < slowing_radius {
+        max_speed * distance / slowing_radius
+    } else {
+        max_speed
+    };
+
+    let desired = scale(to_target, speed / distance);
+    desired - velocity
+}
+
+/// `id` is ours and `neighbours` are the ids and centers of everyone else, the ids only
+/// matter when two of us are stacked exactly on top of each other
+pub fn separate(pos: Vec2<f32>, id: u32, neighbours: &[(u32, Vec2<f32>)], radius: f32) -> Vec2<f32> {
+    let mut push = Vec2{x: 0.0, y: 0.0};
+    for &(other_id, other) in neighbours {
+        let away = pos - other;
+        let distance = length(away);
+        if distance >= radius {
+            continue;
+        }
+
+        if distance < 0.001 {
+            // perfectly stacked, every pair gets its own line to split along
+            // and the lower id goes one way while the higher goes the other
+            let pair = id.min(other_id).wrapping_mul(31).wrapping_add(id.max(other_id));
+            let angle = pair as f32 * GOLDEN_ANGLE;
+            let side = if id < other_id { radius } else { -radius };
+            push = push + Vec2{x: angle.cos() * side, y: angle.sin() * side};
+            continue;
+        }
+
+        // the closer they are the harder we push
+        let strength = (radius - distance) / radius;
+        push = push + scale(away, strength * radius / distance);
+    }
+
+    push
+}
+
+pub fn avoid_obstacles(pos: Vec2<f32>, velocity: Vec2<f32>, obstacles: &[(Vec2<f32>, Vec2<f32>)], look_ahead: f32, clearance: f32) -> Vec2<f32> {
+    let speed = length(velocity);
+    if speed < 0.001 {
+        return Vec2{x: 0.0, y: 0.0};
+    }
+
+    let heading = scale(velocity, 1.0 / speed);
+    let mut push = Vec2{x: 0.0, y: 0.0};
+
+    for &(obstacle_pos, obstacle_size) in obstacles {
+        let half_size = Vec2{x: obstacle_size.x / 2.0 + clearance, y: obstacle_size.y / 2.0 + clearance};
+        let obstacle_center = obstacle_pos + Vec2{x: obstacle_size.x / 2.0, y: obstacle_size.y / 2.0};
+
+        // sample along our heading and steer sideways away from the first point that lands inside
+        let samples = 4;
+        for i in 1..=samples {
+            let probe = pos + scale(heading, look_ahead * i as f32 / samples as f32);
+            let offset = probe - obstacle_center;
+            if offset.x.abs() > half_size.x || offset.y.abs() > half_size.y {
+                continue;
+            }
+
+            let away = pos - obstacle_center;
+            let side = Vec2{x: -heading.y, y: heading.x};
+            let dir = if dot(side, away) >= 0.0 { 1.0 } else { -1.0 };
+            let urgency = (samples + 1 - i) as f32 / samples as f32;
+            push = push + scale(side, dir * urgency * look_ahead);
+            break;
+        }
+    }
+
+    push
+}
+
+pub fn truncate(vec: Vec2<f32>, max: f32) -> Vec2<f32> {
+    let len = length(vec);
+    if len > max {
+        scale(vec, max / len)
+    } else {
+        vec
+    }
+}
+
+pub fn scale(vec: Vec2<f32>, by: f32) -> Vec2<f32> {
+    Vec2{x: vec.x * by, y: vec.y * by}
+}
+
+pub fn length(vec: Vec2<f32>) -> f32 {
+    (vec.x * vec.x + vec.y * vec.y).sqrt()
+}
+
+fn dot(a: Vec2<f32>, b: Vec2<f32>) -> f32 {
+    a.x * b.x + a.y * b.y
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stacked_pair_splits_apart() {
+        let pos = Vec2{x: 100.0, y: 100.0};
+        let a = separate(pos, 1, &[(2, pos)], 70.0);
+        let b = separate(pos, 2, &[(1, pos)], 70.0);
+
+        assert!(length(a) > 1.0);
+        assert!((a.x + b.x).abs() < 0.001 && (a.y + b.y).abs() < 0.001);
+    }
+
+    #[test]
+    fn stacked_crowd_pushes_everyone_differently() {
+        let pos = Vec2{x: 100.0, y: 100.0};
+        let pushes: Vec<Vec2<f32>> = (0..4)
+            .map(|id| {
+                let others: Vec<(u32, Vec2<f32>)> = (0..4).filter(|o| *o != id).map(|o| (o, pos)).collect();
+                separate(pos, id, &others, 70.0)
+            })
+            .collect();
+
+        for (i, a) in pushes.iter().enumerate() {
+            for b in pushes.iter().skip(i + 1) {
+                assert!(length(*a - *b) > 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn far_neighbours_are_ignored() {
+        let push = separate(Vec2{x: 0.0, y: 0.0}, 0, &[(1, Vec2{x: 200.0, y: 0.0})], 70.0);
+        assert_eq!((push.x, push.y), (0.0, 0.0));
+    }
+}
@@ -4,11 +4,19 @@ use bottomless_pit::text::TextMaterial;
 use bottomless_pit::vectors::Vec2;
 use bottomless_pit::render::RenderInformation;
 
+use crate::viewport::Viewport;
+
 pub struct Text {
     text: TextMaterial,
+    /// on the logical screen
     pub pos: Vec2<f32>,
+    /// on the logical screen, however big the window makes it
     pub size: Vec2<u32>,
-    colour: Colour
+    colour: Colour,
+    content: String,
+    font_size: f32,
+    /// the viewport scale the glyphs were last rendered at
+    scale: f32,
 }
 
 impl Text {
@@ -21,17 +29,62 @@ impl Text {
             pos,
             size,
             colour,
+            content: text.to_string(),
+            font_size: scale,
+            scale: 1.0,
         }
     }
 
-    pub fn draw<'p, 'o>(&'o mut self, render_handle: &mut RenderInformation<'p, 'o>) where 'o: 'p {
-        self.text.add_instance(self.pos, Colour::WHITE, &render_handle);
+    pub fn draw<'p, 'o>(&'o mut self, render_handle: &mut RenderInformation<'p, 'o>, viewport: &Viewport) where 'o: 'p {
+        self.draw_at(render_handle, viewport.to_screen(self.pos), Colour::WHITE);
+    }
+
+    /// for text that isn't on the logical screen, `pos` is already in the window. The tint
+    /// only really shows on text made in white
+    pub fn draw_at<'p, 'o>(&'o mut self, render_handle: &mut RenderInformation<'p, 'o>, pos: Vec2<f32>, tint: Colour) where 'o: 'p {
+        self.text.add_instance(pos, tint, &render_handle);
 
         self.text.draw(render_handle);
     }
 
+    /// rebuilds the glyphs at the viewport's size so scaled up text stays sharp, only does
+    /// anything when the scale actually changed
+    pub fn fit(&mut self, viewport: &Viewport, engine: &mut Engine) {
+        let scale = viewport.get_scale();
+        if scale == self.scale {
+            return;
+        }
+
+        let font_size = self.font_size * scale;
+        self.text = TextMaterial::new(&self.content, self.colour, font_size, font_size * 1.2, engine);
+        self.scale = scale;
+        self.measure();
+    }
+
+    /// the area around the text that gets highlighted and clicked when it's used as a button
+    pub fn button_rect(&self) -> (Vec2<f32>, Vec2<f32>) {
+        let pos = self.pos - Vec2{x: 10.0, y: 10.0};
+        let size = Vec2{x: self.size.x as f32 + 20.0, y: self.size.y as f32 + 20.0};
+        (pos, size)
+    }
+
+    pub fn is_clicked(&self, mouse_pos: Vec2<f32>) -> bool {
+        let (pos, size) = self.button_rect();
+        crate::collision::point_in_rect(size, pos, mouse_pos)
+    }
+
     pub fn change_text(&mut self, new_text: &str, engine: &mut Engine) {
         self.text.set_text(new_text, self.colour, engine);
         self.text.prepare(engine);
+        self.content = new_text.to_string();
+        self.measure();
     }
-}
\ No newline at end of file
+
+    fn measure(&mut self) {
+        let measured = self.text.get_measurements();
+        self.size = Vec2{
+            x: (measured.x as f32 / self.scale).round() as u32,
+            y: (measured.y as f32 / self.scale).round() as u32,
+        };
+    }
+}
@@ -0,0 +1,154 @@
+/// Everything about the player that upgrades are allowed to change
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stat {
+    /// multiplier on the rolling pin's size
+    Reach,
+    /// multiplier on how fast charge builds up
+    ChargeSpeed,
+    /// multiplier on the damage of reflected butter
+    ReflectDamage,
+    /// pixels per second
+    MoveSpeed,
+    MaxHp,
+    /// multiplier on the area around the pin that catches butter
+    ReflectWindow,
+}
+
+impl Stat {
+    const COUNT: usize = 6;
+
+    fn base(&self) -> f32 {
+        match self {
+            Self::Reach => 1.0,
+            Self::ChargeSpeed => 1.0,
+            Self::ReflectDamage => 1.0,
+            Self::MoveSpeed => 40.0,
+            Self::MaxHp => 3.0,
+            Self::ReflectWindow => 1.0,
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Modifier {
+    Add(Stat, f32),
+    Multiply(Stat, f32),
+}
+
+impl Modifier {
+    fn stat(&self) -> Stat {
+        match self {
+            Self::Add(stat, _) => *stat,
+            Self::Multiply(stat, _) => *stat,
+        }
+    }
+}
+
+/// Player stats are the base values with every modifier stacked on top,
+/// all the adds are applied first and then all the multipliers.
+#[derive(Clone, Debug)]
+pub struct Stats {
+    modifiers: Vec<Modifier>,
+    cache: [f32; Stat::COUNT],
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        let mut stats = Self {
+            modifiers: Vec::new(),
+            cache: [0.0; Stat::COUNT],
+        };
+        stats.recalculate();
+        stats
+    }
+
+    pub fn get(&self, stat: Stat) -> f32 {
+        self.cache[stat.index()]
+    }
+
+    pub fn add_modifier(&mut self, modifier: Modifier) {
+        self.modifiers.push(modifier);
+        self.recalculate();
+    }
+
+    pub fn clear(&mut self) {
+        self.modifiers.clear();
+        self.recalculate();
+    }
+
+    fn recalculate(&mut self) {
+        let stats = [Stat::Reach, Stat::ChargeSpeed, Stat::ReflectDamage, Stat::MoveSpeed, Stat::MaxHp, Stat::ReflectWindow];
+        for stat in stats {
+            let mut add = 0.0;
+            let mut multiply = 1.0;
+            for modifier in self.modifiers.iter().filter(|m| m.stat() == stat) {
+                match modifier {
+                    Modifier::Add(_, amount) => add += amount,
+                    Modifier::Multiply(_, amount) => multiply *= amount,
+                }
+            }
+
+            self.cache[stat.index()] = (stat.base() + add) * multiply;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Upgrade {
+    LongerPin,
+    QuickCharge,
+    HeavyParry,
+    LightFeet,
+    ExtraHeart,
+}
+
+impl Upgrade {
+    pub const ALL: [Self; 5] = [Self::LongerPin, Self::QuickCharge, Self::HeavyParry, Self::LightFeet, Self::ExtraHeart];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::LongerPin => "Longer Pin",
+            Self::QuickCharge => "Quick Charge",
+            Self::HeavyParry => "Heavy Parry",
+            Self::LightFeet => "Light Feet",
+            Self::ExtraHeart => "Extra Heart",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::LongerPin => "+15% rolling pin reach",
+            Self::QuickCharge => "Charge your parry 25% faster",
+            Self::HeavyParry => "Reflected butter hits 20% harder",
+            Self::LightFeet => "Move 15% faster",
+            Self::ExtraHeart => "+1 max heart",
+        }
+    }
+
+    /// every copy you already own makes the next one pricier
+    pub fn cost(&self, owned: u32) -> u32 {
+        let base = match self {
+            Self::LongerPin => 30,
+            Self::QuickCharge => 25,
+            Self::HeavyParry => 35,
+            Self::LightFeet => 20,
+            Self::ExtraHeart => 40,
+        };
+
+        base + base * owned / 2
+    }
+
+    pub fn modifier(&self) -> Modifier {
+        match self {
+            Self::LongerPin => Modifier::Multiply(Stat::Reach, 1.15),
+            Self::QuickCharge => Modifier::Multiply(Stat::ChargeSpeed, 1.25),
+            Self::HeavyParry => Modifier::Multiply(Stat::ReflectDamage, 1.2),
+            Self::LightFeet => Modifier::Multiply(Stat::MoveSpeed, 1.15),
+            Self::ExtraHeart => Modifier::Add(Stat::MaxHp, 1.0),
+        }
+    }
+}
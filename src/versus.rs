@@ -0,0 +1,271 @@
+use bottomless_pit::colour::Colour;
+use bottomless_pit::engine_handle::Engine;
+use bottomless_pit::material::{Material, MaterialBuilder};
+use bottomless_pit::render::RenderInformation;
+use bottomless_pit::texture::Texture;
+use bottomless_pit::vectors::Vec2;
+
+use crate::arena::Arena;
+use crate::camera::Camera;
+use crate::difficulty::Difficulty;
+use crate::enemy::Butter;
+use crate::event::GameEvent;
+use crate::particle::Particles;
+use crate::player::{Controls, Player, PlayerInput, PlayerSprites};
+use crate::text::Text;
+use crate::viewport::Viewport;
+
+/// best of five
+pub const ROUNDS_TO_WIN: u32 = 3;
+const ROUND_HEARTS: f32 = 3.0;
+const LAUNCHER_POS: Vec2<f32> = Vec2{x: 385.0, y: 20.0};
+const LAUNCHER_SIZE: Vec2<f32> = Vec2{x: 30.0, y: 30.0};
+const LAUNCH_SPEED: f32 = 300.0;
+/// seconds between the launcher's shots, it waits for the last butter to be gone first
+const LAUNCH_INTERVAL: f32 = 1.5;
+/// every parry sends the butter back this much faster
+const VOLLEY_SPEEDUP: f32 = 1.15;
+const ROUND_BREAK: f32 = 2.0;
+
+/// Two players on one keyboard knocking butter at each other. A launcher in the middle
+/// serves the butter and every parry sends it at the other player a little faster, the
+/// first to take all of a round's hearts off the other wins it
+pub struct Versus {
+    players: Vec<Player>,
+    player_sprites: PlayerSprites,
+    butters: Vec<Butter>,
+    arena: Arena,
+    background: Material,
+    obstacle_material: Material,
+    butter_texture: Material,
+    launcher_material: Material,
+    particles: Particles,
+    particle_material: Material,
+    camera: Camera,
+    /// score, round, message
+    text: Vec<Text>,
+    launch_timer: f32,
+    /// the launcher takes turns serving at each player
+    next_serve: usize,
+    wins: [u32; 2],
+    round: u32,
+    /// counts down the pause after a round is decided
+    round_break: Option<f32>,
+    events: Vec<GameEvent>,
+}
+
+impl Versus {
+    pub fn new(arena: Arena, engine_handle: &mut Engine) -> Self {
+        let background_tex = Texture::new(engine_handle, &arena.background);
+        let background = MaterialBuilder::new().add_texture(background_tex).build(engine_handle);
+        let butter_tex = Texture::new(engine_handle, "assets/butter.png");
+        let butter_texture = MaterialBuilder::new().add_texture(butter_tex).build(engine_handle);
+
+        let players = vec![
+            Player::new(Vec2{x: 150.0, y: 400.0}, 0, Controls::Mouse),
+            Player::new(Vec2{x: 600.0, y: 400.0}, 1, Controls::Keyboard),
+        ];
+
+        let score = Text::new("", 40.0, Vec2{x: 0.0, y: 60.0}, Colour::BLACK, engine_handle);
+        let round = Text::new("", 30.0, Vec2{x: 0.0, y: 105.0}, Colour::BLACK, engine_handle);
+        let message = Text::new("", 45.0, Vec2{x: 0.0, y: 360.0}, Colour::BLACK, engine_handle);
+
+        let mut versus = Self {
+            players,
+            player_sprites: PlayerSprites::new(engine_handle),
+            butters: Vec::new(),
+            arena,
+            background,
+            obstacle_material: MaterialBuilder::new().build(engine_handle),
+            butter_texture,
+            launcher_material: MaterialBuilder::new().build(engine_handle),
+            particles: Particles::new(),
+            particle_material: MaterialBuilder::new().build(engine_handle),
+            camera: Camera::new(),
+            text: vec![score, round, message],
+            launch_timer: LAUNCH_INTERVAL,
+            next_serve: 0,
+            wins: [0, 0],
+            round: 1,
+            round_break: None,
+            events: Vec::new(),
+        };
+        versus.restart(engine_handle);
+        versus
+    }
+
+    /// a fresh best of five
+    pub fn restart(&mut self, engine_handle: &mut Engine) {
+        self.wins = [0, 0];
+        self.round = 1;
+        self.next_serve = 0;
+        self.particles.clear();
+        self.start_round(engine_handle);
+    }
+
+    /// returns true once someone has won the match and the last round's pause is over
+    pub fn update(&mut self, engine_handle: &mut Engine, dt: f32) -> bool {
+        self.particles.update(dt);
+        self.camera.update(dt);
+        if let Some(timer) = self.round_break.as_mut() {
+            *timer -= dt;
+            if *timer > 0.0 {
+                return false;
+            }
+            if self.winner().is_some() {
+                return true;
+            }
+            self.round += 1;
+            self.start_round(engine_handle);
+            return false;
+        }
+
+        // remember which butter was still up for grabs so we can tell what got parried this frame
+        let parryable: Vec<bool> = self.butters.iter().map(|b| !b.is_reflected()).collect();
+        let first_event = self.events.len();
+
+        for player in self.players.iter_mut() {
+            let mut input: PlayerInput = player.read_input(engine_handle);
+            input.aim = input.aim.map(|aim| self.camera.to_world(aim));
+            player.update(&input, dt, &mut self.butters, &self.arena, &mut self.events);
+        }
+        self.volley(&parryable);
+
+        self.serve(dt);
+        self.butters.iter_mut().for_each(|b| b.update(dt, &mut self.players, &mut [], &mut self.arena, &mut self.events));
+        self.butters.retain(|b| b.valid && self.arena.contains(b.pos));
+        self.arena.hazards.update(dt);
+        self.arena.hazards.burn_players(&mut self.players, &mut self.events);
+        self.particles.handle_events(&self.events[first_event..]);
+        self.camera.handle_events(&self.events[first_event..]);
+
+        // whoever went down first this frame loses the round
+        let loser = self.events[first_event..].iter().find_map(|e| match e {
+            GameEvent::PlayerDied{player} => Some(*player),
+            _ => None,
+        });
+        if let Some(loser) = loser {
+            self.end_round(1 - loser, engine_handle);
+        }
+
+        false
+    }
+
+    pub fn draw<'p, 'o>(&'o mut self, render_handle: &mut RenderInformation<'p, 'o>) where 'o: 'p {
+        let (background_pos, background_size) = self.camera.rect(Vec2{x: 0.0, y: 0.0}, self.arena.size);
+        self.background.add_rectangle(background_pos, background_size, self.arena.tint, render_handle);
+        self.background.draw(render_handle);
+        self.arena.draw(render_handle, &mut self.obstacle_material, &self.camera);
+        self.obstacle_material.draw(render_handle);
+
+        let (launcher_pos, launcher_size) = self.camera.rect(LAUNCHER_POS, LAUNCHER_SIZE);
+        self.launcher_material.add_rectangle(launcher_pos, launcher_size, Colour::from_rgba(90.0, 90.0, 90.0, 255.0), render_handle);
+        self.launcher_material.draw(render_handle);
+
+        self.butters.iter().for_each(|b| b.draw(render_handle, &mut self.butter_texture, &self.camera));
+        self.butter_texture.draw(render_handle);
+        self.particles.draw(render_handle, &mut self.particle_material, &self.camera);
+        self.particle_material.draw(render_handle);
+        self.players.iter().for_each(|p| p.draw(render_handle, &mut self.player_sprites, &self.camera));
+        self.player_sprites.draw(render_handle);
+
+        // the round message only shows during the break
+        let shown_text = if self.round_break.is_some() {
+            self.text.len()
+        } else {
+            self.text.len() - 1
+        };
+        let hud = self.camera.get_viewport();
+        self.text[..shown_text].iter_mut().for_each(|t| t.draw(render_handle, &hud));
+    }
+
+    /// hands over everything that happened since the last call
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// call whenever the window might have changed size
+    pub fn set_viewport(&mut self, viewport: Viewport, engine_handle: &mut Engine) {
+        self.camera.set_viewport(viewport);
+        self.text.iter_mut().for_each(|t| t.fit(&viewport, engine_handle));
+    }
+
+    pub fn set_screen_shake(&mut self, enabled: bool) {
+        self.camera.set_shake_enabled(enabled);
+    }
+
+    pub fn get_round(&self) -> u32 {
+        self.round
+    }
+
+    pub fn get_wins(&self) -> [u32; 2] {
+        self.wins
+    }
+
+    /// which player took the match, if anyone has yet
+    pub fn winner(&self) -> Option<usize> {
+        self.wins.iter().position(|w| *w >= ROUNDS_TO_WIN)
+    }
+
+    fn start_round(&mut self, engine_handle: &mut Engine) {
+        self.players.iter_mut().for_each(|p| p.restart(Difficulty::Normal, ROUND_HEARTS));
+        self.butters.clear();
+        self.arena.hazards.reset();
+        self.launch_timer = LAUNCH_INTERVAL;
+        self.round_break = None;
+        self.events.push(GameEvent::WaveStarted);
+
+        self.text[0].change_text(&format!("P1  {} - {}  P2", self.wins[0], self.wins[1]), engine_handle);
+        self.text[0].pos.x = 400.0 - self.text[0].size.x as f32 / 2.0;
+        self.text[1].change_text(&format!("Round {}", self.round), engine_handle);
+        self.text[1].pos.x = 400.0 - self.text[1].size.x as f32 / 2.0;
+    }
+
+    fn end_round(&mut self, winner: usize, engine_handle: &mut Engine) {
+        self.wins[winner] += 1;
+        self.round_break = Some(ROUND_BREAK);
+
+        self.text[0].change_text(&format!("P1  {} - {}  P2", self.wins[0], self.wins[1]), engine_handle);
+        self.text[0].pos.x = 400.0 - self.text[0].size.x as f32 / 2.0;
+        self.text[2].change_text(&format!("Player {} takes round {}!", winner + 1, self.round), engine_handle);
+        self.text[2].pos.x = 400.0 - self.text[2].size.x as f32 / 2.0;
+    }
+
+    /// the launcher serves a new butter once the last one is gone
+    fn serve(&mut self, dt: f32) {
+        if !self.butters.is_empty() {
+            return;
+        }
+
+        self.launch_timer -= dt;
+        if self.launch_timer > 0.0 {
+            return;
+        }
+
+        let start = LAUNCHER_POS + Vec2{x: LAUNCHER_SIZE.x / 2.0, y: LAUNCHER_SIZE.y / 2.0};
+        let target = self.players[self.next_serve].get_center();
+        self.butters.push(Butter::new(start, target, LAUNCH_SPEED));
+        self.events.push(GameEvent::ButterThrown);
+        self.next_serve = 1 - self.next_serve;
+        self.launch_timer = LAUNCH_INTERVAL;
+    }
+
+    /// whatever got parried this frame goes at the other player instead of where the pin was aimed
+    fn volley(&mut self, parryable: &[bool]) {
+        for (butter, _) in self.butters.iter_mut().zip(parryable).filter(|(b, was)| **was && b.is_reflected()) {
+            let center = butter.get_center();
+            let parried_by = self.players
+                .iter()
+                .map(|p| {
+                    let pin = p.get_weapon_center();
+                    (pin.x - center.x).powi(2) + (pin.y - center.y).powi(2)
+                })
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(i, _)| i)
+                .unwrap_or(0);
+
+            butter.volley(self.players[1 - parried_by].get_center(), VOLLEY_SPEEDUP);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use bottomless_pit::vectors::Vec2;
+
+/// Everything is laid out on a screen this size, the viewport scales it to whatever the window is
+pub const LOGICAL_SIZE: Vec2<f32> = Vec2{x: 800.0, y: 800.0};
+
+/// Where the logical screen ends up in the window. It's scaled as big as it fits without
+/// stretching and centred, whatever's left over either side is the letterbox
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    scale: f32,
+    offset: Vec2<f32>,
+}
+
+impl Viewport {
+    pub fn new() -> Self {
+        Self {
+            scale: 1.0,
+            offset: Vec2{x: 0.0, y: 0.0},
+        }
+    }
+
+    pub fn fit(window_size: Vec2<u32>) -> Self {
+        let window = Vec2{x: window_size.x.max(1) as f32, y: window_size.y.max(1) as f32};
+        let scale = (window.x / LOGICAL_SIZE.x).min(window.y / LOGICAL_SIZE.y);
+
+        Self {
+            scale,
+            offset: Vec2{
+                x: (window.x - LOGICAL_SIZE.x * scale) / 2.0,
+                y: (window.y - LOGICAL_SIZE.y * scale) / 2.0,
+            },
+        }
+    }
+
+    pub fn get_scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn to_screen(self, pos: Vec2<f32>) -> Vec2<f32> {
+        Vec2{x: pos.x * self.scale + self.offset.x, y: pos.y * self.scale + self.offset.y}
+    }
+
+    /// for the mouse, window pixels back onto the logical screen
+    pub fn to_logical(self, pos: Vec2<f32>) -> Vec2<f32> {
+        Vec2{x: (pos.x - self.offset.x) / self.scale, y: (pos.y - self.offset.y) / self.scale}
+    }
+
+    /// a logical rect as (pos, size) in the window
+    pub fn rect(&self, pos: Vec2<f32>, size: Vec2<f32>) -> (Vec2<f32>, Vec2<f32>) {
+        (self.to_screen(pos), Vec2{x: size.x * self.scale, y: size.y * self.scale})
+    }
+
+    /// The bars around the logical screen as (pos, size) in the window, left, right, top then
+    /// bottom. Anything that scrolls past the edge of the screen gets covered up by these
+    pub fn letterbox(&self) -> [(Vec2<f32>, Vec2<f32>); 4] {
+        let screen = Vec2{x: LOGICAL_SIZE.x * self.scale, y: LOGICAL_SIZE.y * self.scale};
+        let window = Vec2{x: screen.x + self.offset.x * 2.0, y: screen.y + self.offset.y * 2.0};
+
+        [
+            (Vec2{x: 0.0, y: 0.0}, Vec2{x: self.offset.x, y: window.y}),
+            (Vec2{x: self.offset.x + screen.x, y: 0.0}, Vec2{x: self.offset.x, y: window.y}),
+            (Vec2{x: 0.0, y: 0.0}, Vec2{x: window.x, y: self.offset.y}),
+            (Vec2{x: 0.0, y: self.offset.y + screen.y}, Vec2{x: window.x, y: self.offset.y}),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letterbox_fills_the_sides_of_a_wide_window() {
+        let viewport = Viewport::fit(Vec2{x: 1600, y: 800});
+        let [left, right, top, bottom] = viewport.letterbox();
+
+        assert_eq!(left, (Vec2{x: 0.0, y: 0.0}, Vec2{x: 400.0, y: 800.0}));
+        assert_eq!(right, (Vec2{x: 1200.0, y: 0.0}, Vec2{x: 400.0, y: 800.0}));
+        assert_eq!(top.1.y, 0.0);
+        assert_eq!(bottom.1.y, 0.0);
+    }
+
+    #[test]
+    fn letterbox_fills_the_top_and_bottom_of_a_tall_window() {
+        let viewport = Viewport::fit(Vec2{x: 400, y: 600});
+        let [left, right, top, bottom] = viewport.letterbox();
+
+        assert_eq!(left.1.x, 0.0);
+        assert_eq!(right.1.x, 0.0);
+        assert_eq!(top, (Vec2{x: 0.0, y: 0.0}, Vec2{x: 400.0, y: 100.0}));
+        assert_eq!(bottom, (Vec2{x: 0.0, y: 500.0}, Vec2{x: 400.0, y: 100.0}));
+    }
+}